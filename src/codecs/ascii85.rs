@@ -0,0 +1,375 @@
+use super::codec::Codec;
+use super::error::Error;
+use super::score;
+use crate::Format;
+
+const Z85: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+const BTOA_BEGIN: &str = "xbtoa Begin";
+const BTOA_END: &str = "xbtoa End";
+const BTOA_LINE_LENGTH: usize = 78;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Ascii85Variant {
+    // Adobe framing with <~ and ~>, and the z zero-group shortcut
+    Adobe,
+    // The original btoa tool, with its xbtoa Begin / End framing and
+    // the y shortcut for four spaces
+    Btoa,
+    // ZeroMQ's RFC 32 alphabet, without shortcuts or framing
+    Z85,
+}
+
+pub struct Ascii85Codec {
+    pub variant: Ascii85Variant,
+}
+
+impl Codec for Ascii85Codec {
+    fn format(&self) -> Format {
+        match self.variant {
+            Ascii85Variant::Adobe => Format::Ascii85,
+            Ascii85Variant::Btoa => Format::Btoa,
+            Ascii85Variant::Z85 => Format::Z85,
+        }
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        match self.variant {
+            Ascii85Variant::Adobe => self.decode_body(Self::strip_adobe_frame(&s)?),
+            Ascii85Variant::Btoa => self.decode_btoa(s),
+            Ascii85Variant::Z85 => self.decode_body(&s),
+        }
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        match self.variant {
+            Ascii85Variant::Adobe => Ok(format!("<~{}~>", self.encode_body(&data)).into_bytes()),
+            Ascii85Variant::Btoa => Ok(self.encode_btoa(data).into_bytes()),
+            Ascii85Variant::Z85 if !data.len().is_multiple_of(4) => Err(Error::new(
+                "Z85 input must be a multiple of 4 bytes".to_string(),
+            )),
+            Ascii85Variant::Z85 => Ok(self.encode_body(&data).into_bytes()),
+        }
+    }
+
+    fn inferrable(&self) -> bool {
+        // Z85 has no framing to recognize it by
+        self.variant != Ascii85Variant::Z85
+    }
+
+    fn confidence(&self, _s: &[u8], decoded: &[u8]) -> f64 {
+        // Only reached for framed input, which is hard to produce by accident
+        score::weigh(0.95, decoded)
+    }
+}
+
+impl Ascii85Codec {
+    fn char_to_val(&self, c: u8) -> Result<u32, Error> {
+        match self.variant {
+            Ascii85Variant::Z85 => Z85.iter().position(|&a| a == c).map(|v| v as u32),
+            _ if (b'!'..=b'u').contains(&c) => Some((c - b'!') as u32),
+            _ => None,
+        }
+        .ok_or_else(|| Error::new(format!("Invalid char: {}", c as char)))
+    }
+
+    fn val_to_char(&self, v: u32) -> char {
+        match self.variant {
+            Ascii85Variant::Z85 => Z85[v as usize] as char,
+            _ => (b'!' + v as u8) as char,
+        }
+    }
+
+    // Returns the four bytes a group is shortened to, if any
+    fn shortcut(&self, c: u8) -> Option<[u8; 4]> {
+        match (self.variant, c) {
+            (Ascii85Variant::Adobe, b'z') | (Ascii85Variant::Btoa, b'z') => Some([0; 4]),
+            (Ascii85Variant::Btoa, b'y') => Some([0x20; 4]),
+            _ => None,
+        }
+    }
+
+    // The opening <~ is optional, as in PostScript, but the ~> end marker is not
+    fn strip_adobe_frame(s: &[u8]) -> Result<&[u8], Error> {
+        let s = s.trim_ascii();
+        let s = s.strip_prefix(b"<~").unwrap_or(s);
+        s.strip_suffix(b"~>")
+            .ok_or_else(|| Error::new("Missing ~> end marker for ascii85".to_string()))
+    }
+
+    fn decode_body(&self, s: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::with_capacity(s.len() * 4 / 5);
+        let mut group = Vec::with_capacity(5);
+        for &c in s.iter().filter(|c| !c.is_ascii_whitespace()) {
+            match self.shortcut(c) {
+                Some(bytes) if group.is_empty() => out.extend_from_slice(&bytes),
+                _ => {
+                    group.push(self.char_to_val(c)?);
+                    if group.len() == 5 {
+                        out.extend_from_slice(&Self::group_to_bytes(&group)?);
+                        group.clear();
+                    }
+                }
+            }
+        }
+
+        // A final group of n characters holds n - 1 bytes
+        match group.len() {
+            0 => Ok(out),
+            n if n == 1 || self.variant == Ascii85Variant::Z85 => Err(Error::new(
+                "Invalid number of characters in final ascii85 group".to_string(),
+            )),
+            n => {
+                group.resize(5, 84);
+                out.extend_from_slice(&Self::group_to_bytes(&group)?[..n - 1]);
+                Ok(out)
+            }
+        }
+    }
+
+    fn group_to_bytes(group: &[u32]) -> Result<[u8; 4], Error> {
+        let value = group.iter().fold(0u64, |acc, &v| acc * 85 + v as u64);
+        if value > u32::MAX as u64 {
+            Err(Error::new("Ascii85 group overflows 32 bits".to_string()))
+        } else {
+            Ok((value as u32).to_be_bytes())
+        }
+    }
+
+    fn encode_body(&self, data: &[u8]) -> String {
+        data.chunks(4)
+            .flat_map(|chunk| {
+                let mut group = [0u8; 4];
+                group[..chunk.len()].copy_from_slice(chunk);
+                match self.shortcut_for(&group) {
+                    Some(c) if chunk.len() == 4 => vec![c],
+                    _ => {
+                        let mut value = u32::from_be_bytes(group);
+                        let mut chars = vec!['\0'; 5];
+                        for ch in chars.iter_mut().rev() {
+                            *ch = self.val_to_char(value % 85);
+                            value /= 85;
+                        }
+                        // A final group of n bytes is written as n + 1 characters
+                        chars.truncate(chunk.len() + 1);
+                        chars
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn shortcut_for(&self, group: &[u8; 4]) -> Option<char> {
+        [b'z', b'y']
+            .iter()
+            .find(|&&c| self.shortcut(c).as_ref() == Some(group))
+            .map(|&c| c as char)
+    }
+
+    fn decode_btoa(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let s = String::from_utf8(s)
+            .map_err(|_| Error::new("Input to btoa was invalid utf8".to_string()))?;
+        let mut lines = s.lines().map(str::trim).filter(|l| !l.is_empty());
+        if lines.next() != Some(BTOA_BEGIN) {
+            return Err(Error::new("Missing xbtoa Begin line".to_string()));
+        }
+
+        let mut body = String::new();
+        let trailer = loop {
+            match lines.next() {
+                Some(line) if line.starts_with(BTOA_END) => break BtoaTrailer::parse(line)?,
+                Some(line) => body.push_str(line),
+                None => return Err(Error::new("Missing xbtoa End line".to_string())),
+            }
+        };
+
+        // btoa always writes whole groups, so the trailer's length tells us
+        // how much of the last group was padding
+        let mut data = self.decode_body(body.as_bytes())?;
+        if trailer.len > data.len() || data.len() - trailer.len > 3 {
+            return Err(Error::new("xbtoa length does not match data".to_string()));
+        }
+        data.truncate(trailer.len);
+        if BtoaTrailer::new(&data) != trailer {
+            return Err(Error::new("xbtoa checksums do not match".to_string()));
+        }
+        Ok(data)
+    }
+
+    fn encode_btoa(&self, data: Vec<u8>) -> String {
+        let trailer = BtoaTrailer::new(&data);
+        let mut padded = data;
+        padded.resize(padded.len().div_ceil(4) * 4, 0);
+        let body = self.encode_body(&padded);
+
+        let mut out = format!("{}\n", BTOA_BEGIN);
+        body.as_bytes().chunks(BTOA_LINE_LENGTH).for_each(|line| {
+            // The body is ascii, so splitting it by byte is safe
+            out.push_str(&String::from_utf8_lossy(line));
+            out.push('\n');
+        });
+        out.push_str(&trailer.to_string());
+        out
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+struct BtoaTrailer {
+    len: usize,
+    eor: u32,
+    sum: u32,
+    rot: u32,
+}
+
+impl BtoaTrailer {
+    fn new(data: &[u8]) -> Self {
+        data.iter().fold(
+            Self {
+                len: data.len(),
+                eor: 0,
+                sum: 0,
+                rot: 0,
+            },
+            |t, &c| Self {
+                eor: t.eor ^ c as u32,
+                sum: t.sum.wrapping_add(c as u32 + 1),
+                rot: t.rot.rotate_left(1).wrapping_add(c as u32),
+                ..t
+            },
+        )
+    }
+
+    fn parse(line: &str) -> Result<Self, Error> {
+        let err = || Error::new(format!("Malformed xbtoa End line: {}", line));
+        let hex = |s: &str| u32::from_str_radix(s, 16).map_err(|_| err());
+        match line.split_whitespace().collect::<Vec<_>>()[..] {
+            ["xbtoa", "End", "N", len, len_hex, "E", eor, "S", sum, "R", rot] => {
+                let len = len.parse::<usize>().map_err(|_| err())?;
+                if hex(len_hex)? as usize != len {
+                    return Err(err());
+                }
+                Ok(Self {
+                    len,
+                    eor: hex(eor)?,
+                    sum: hex(sum)?,
+                    rot: hex(rot)?,
+                })
+            }
+            _ => Err(err()),
+        }
+    }
+}
+
+impl std::fmt::Display for BtoaTrailer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} N {} {:x} E {:x} S {:x} R {:x}",
+            BTOA_END, self.len, self.len, self.eor, self.sum, self.rot
+        )
+    }
+}
+
+#[test]
+fn encode() {
+    use std::collections::HashMap;
+
+    let tests: HashMap<(&str, Ascii85Variant), Vec<u8>> = [
+        (("<~~>", Ascii85Variant::Adobe), "".as_bytes().to_vec()),
+        (
+            ("<~BOu!rDZ~>", Ascii85Variant::Adobe),
+            "hello".as_bytes().to_vec(),
+        ),
+        (
+            ("<~zBOu!rD]g/F+EqaECh*~>", Ascii85Variant::Adobe),
+            "\0\0\0\0hello    world".as_bytes().to_vec(),
+        ),
+        (
+            (
+                "xbtoa Begin\nBOu!rDZBb;\nxbtoa End N 5 5 E 62 S 219 R c9f",
+                Ascii85Variant::Btoa,
+            ),
+            "hello".as_bytes().to_vec(),
+        ),
+        (
+            ("HelloWorld", Ascii85Variant::Z85),
+            vec![0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B],
+        ),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    for ((expected, variant), bytes) in tests {
+        let codec = Ascii85Codec { variant };
+        assert_eq!(expected.as_bytes().to_vec(), codec.encode(bytes).unwrap());
+    }
+}
+
+#[test]
+fn decode() {
+    use std::collections::HashMap;
+
+    let tests: HashMap<(&str, Ascii85Variant), Result<Vec<u8>, Error>> = [
+        (
+            ("<~BOu!rDZ~>", Ascii85Variant::Adobe),
+            Ok("hello".as_bytes().to_vec()),
+        ),
+        (
+            ("BOu!r\nDZ~>", Ascii85Variant::Adobe),
+            Ok("hello".as_bytes().to_vec()),
+        ),
+        (
+            ("<~zBOu!rD]g/F+EqaECh*~>", Ascii85Variant::Adobe),
+            Ok("\0\0\0\0hello    world".as_bytes().to_vec()),
+        ),
+        (
+            (
+                "xbtoa Begin\nyBOu!rDZBb;\nxbtoa End N 9 9 E 62 S 29d R 489f",
+                Ascii85Variant::Btoa,
+            ),
+            Ok("    hello".as_bytes().to_vec()),
+        ),
+        (
+            ("HelloWorld", Ascii85Variant::Z85),
+            Ok(vec![0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B]),
+        ),
+        (
+            ("<~BOu!rDZ", Ascii85Variant::Adobe),
+            Err(Error::new("Missing ~> end marker for ascii85".to_string())),
+        ),
+        (
+            ("<~BOu!rD~~>", Ascii85Variant::Adobe),
+            Err(Error::new("Invalid char: ~".to_string())),
+        ),
+        (
+            ("<~B~>", Ascii85Variant::Adobe),
+            Err(Error::new(
+                "Invalid number of characters in final ascii85 group".to_string(),
+            )),
+        ),
+        (
+            ("<~uuuuu~>", Ascii85Variant::Adobe),
+            Err(Error::new("Ascii85 group overflows 32 bits".to_string())),
+        ),
+        (
+            (
+                "xbtoa Begin\nBOu!rDZBb;\nxbtoa End N 5 5 E 62 S 219 R c9e",
+                Ascii85Variant::Btoa,
+            ),
+            Err(Error::new("xbtoa checksums do not match".to_string())),
+        ),
+        (
+            ("BOu!rDZBb;", Ascii85Variant::Btoa),
+            Err(Error::new("Missing xbtoa Begin line".to_string())),
+        ),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    for ((data, variant), expected) in tests {
+        let codec = Ascii85Codec { variant };
+        assert_eq!(expected, codec.decode(data.as_bytes().to_vec()));
+    }
+}
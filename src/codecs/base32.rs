@@ -0,0 +1,396 @@
+use super::codec::{Codec, Framing};
+use super::error::Error;
+use super::score;
+use crate::Format;
+
+const STANDARD: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const EXTENDED_HEX: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const ZBASE32: &[u8; 32] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
+// Crockford check symbols for values 32 through 36
+const CROCKFORD_CHECK: &[u8; 5] = b"*~$=U";
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Base32Variant {
+    // RFC 4648 section 6
+    Standard,
+    // RFC 4648 section 7, "base32hex"
+    ExtendedHex,
+    Crockford,
+    // Crockford with a trailing mod 37 check symbol
+    CrockfordCheck,
+    ZBase32,
+}
+
+pub struct Base32Codec {
+    pub variant: Base32Variant,
+}
+
+impl Codec for Base32Codec {
+    fn format(&self) -> Format {
+        match self.variant {
+            Base32Variant::Standard => Format::Base32,
+            Base32Variant::ExtendedHex => Format::Base32Hex,
+            Base32Variant::Crockford => Format::Crockford,
+            Base32Variant::CrockfordCheck => Format::CrockfordCheck,
+            Base32Variant::ZBase32 => Format::ZBase32,
+        }
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let mut s = s
+            .into_iter()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect::<Vec<u8>>();
+        let check = if self.variant == Base32Variant::CrockfordCheck {
+            match s.pop() {
+                Some(c) => Some(Self::check_to_val(c)?),
+                None => return Err(Error::new("Missing Crockford check symbol".to_string())),
+            }
+        } else {
+            None
+        };
+
+        let bytes = self.symbols_to_vals(s).and_then(Self::unpack)?;
+
+        match check {
+            Some(c) if c != Self::check_value(&bytes) => Err(Error::new(
+                "Crockford check symbol does not match".to_string(),
+            )),
+            _ => Ok(bytes),
+        }
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let alphabet = self.alphabet();
+        let mut out = Self::pack(&data)
+            .into_iter()
+            .map(|v| alphabet[v as usize] as char)
+            .collect::<String>();
+        if self.padded() {
+            while !out.len().is_multiple_of(8) {
+                out.push('=');
+            }
+        }
+        if self.variant == Base32Variant::CrockfordCheck {
+            out.push(Self::val_to_check(Self::check_value(&data)));
+        }
+        Ok(out.into_bytes())
+    }
+
+    fn inferrable(&self) -> bool {
+        // The other alphabets overlap too much with hex and plain words
+        self.variant == Base32Variant::Standard
+    }
+
+    fn framing(&self) -> Option<Framing<'_>> {
+        match self.variant {
+            // The check symbol covers the whole value
+            Base32Variant::CrockfordCheck => None,
+            Base32Variant::Crockford => Some(Framing {
+                symbols: 8,
+                bytes: 5,
+                ignored: |&c| c.is_ascii_whitespace() || c == b'-',
+                padding: None,
+                separator: "",
+            }),
+            _ => Some(Framing {
+                symbols: 8,
+                bytes: 5,
+                ignored: u8::is_ascii_whitespace,
+                padding: Some(b'='),
+                separator: "",
+            }),
+        }
+    }
+
+    fn confidence(&self, s: &[u8], decoded: &[u8]) -> f64 {
+        // Uppercase words are valid base 32 too, but rarely padded or a
+        // multiple of 8 symbols long like encoded output always is. Without
+        // either, it's only ever a runner-up.
+        let symbols = s.iter().filter(|c| !c.is_ascii_whitespace()).count();
+        if !s.contains(&b'=') && !symbols.is_multiple_of(8) {
+            return 0.0;
+        }
+        score::weigh(score::BASE32, decoded)
+    }
+}
+
+impl Base32Codec {
+    fn alphabet(&self) -> &'static [u8; 32] {
+        match self.variant {
+            Base32Variant::Standard => STANDARD,
+            Base32Variant::ExtendedHex => EXTENDED_HEX,
+            Base32Variant::Crockford | Base32Variant::CrockfordCheck => CROCKFORD,
+            Base32Variant::ZBase32 => ZBASE32,
+        }
+    }
+
+    fn padded(&self) -> bool {
+        matches!(
+            self.variant,
+            Base32Variant::Standard | Base32Variant::ExtendedHex
+        )
+    }
+
+    // Normalizes the input for the variant and maps each symbol to its 5-bit value
+    fn symbols_to_vals(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let s = match self.variant {
+            // RFC 4648 alphabets are uppercase only, otherwise ordinary
+            // lowercase words would be inferred as base 32
+            Base32Variant::Standard | Base32Variant::ExtendedHex => Self::strip_padding(s)?,
+            Base32Variant::Crockford | Base32Variant::CrockfordCheck => s
+                .into_iter()
+                .filter(|&c| c != b'-')
+                .map(Self::crockford_normalize)
+                .collect(),
+            Base32Variant::ZBase32 => s.to_ascii_lowercase(),
+        };
+
+        let alphabet = self.alphabet();
+        s.into_iter()
+            .map(|c| match alphabet.iter().position(|&a| a == c) {
+                Some(v) => Ok(v as u8),
+                None => Err(Error::new(format!("Invalid char: {}", c as char))),
+            })
+            .collect()
+    }
+
+    fn strip_padding(mut s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let len = s.len();
+        let mut padding = 0;
+        while s.last() == Some(&b'=') {
+            s.pop();
+            padding += 1;
+        }
+        if s.contains(&b'=') {
+            Err(Error::new("Non-tailing padding".to_string()))
+        } else if padding > 0 && (!len.is_multiple_of(8) || ![1, 3, 4, 6].contains(&padding)) {
+            Err(Error::new("Invalid padding for base32 string".to_string()))
+        } else {
+            Ok(s)
+        }
+    }
+
+    // Crockford decoding is case-insensitive and treats I, L and O as 1, 1 and 0
+    fn crockford_normalize(c: u8) -> u8 {
+        match c.to_ascii_uppercase() {
+            b'I' | b'L' => b'1',
+            b'O' => b'0',
+            c => c,
+        }
+    }
+
+    fn unpack(vals: Vec<u8>) -> Result<Vec<u8>, Error> {
+        if ![0, 2, 4, 5, 7].contains(&(vals.len() % 8)) {
+            return Err(Error::new(
+                "Invalid number of characters for base32 string".to_string(),
+            ));
+        }
+        let mut buf: u16 = 0;
+        let mut bits = 0;
+        let mut out = Vec::with_capacity(vals.len() * 5 / 8);
+        for v in vals {
+            buf = (buf << 5) | v as u16;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                out.push((buf >> bits) as u8);
+                buf &= (1 << bits) - 1;
+            }
+        }
+        if buf != 0 {
+            Err(Error::new(
+                "Non-zero trailing bits in base32 string".to_string(),
+            ))
+        } else {
+            Ok(out)
+        }
+    }
+
+    fn pack(data: &[u8]) -> Vec<u8> {
+        let mut buf: u16 = 0;
+        let mut bits = 0;
+        let mut out = Vec::with_capacity(data.len() * 8 / 5 + 1);
+        for &byte in data {
+            buf = (buf << 8) | byte as u16;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                out.push(((buf >> bits) & 0b00011111) as u8);
+            }
+            buf &= (1 << bits) - 1;
+        }
+        if bits > 0 {
+            out.push(((buf << (5 - bits)) & 0b00011111) as u8);
+        }
+        out
+    }
+
+    // The check symbol is the big-endian value of the data modulo 37
+    fn check_value(data: &[u8]) -> u8 {
+        data.iter()
+            .fold(0u32, |acc, &b| (acc * 256 + b as u32) % 37) as u8
+    }
+
+    fn check_to_val(c: u8) -> Result<u8, Error> {
+        let c = Self::crockford_normalize(c);
+        CROCKFORD
+            .iter()
+            .chain(CROCKFORD_CHECK.iter())
+            .position(|&a| a == c)
+            .map(|v| v as u8)
+            .ok_or_else(|| Error::new(format!("Invalid check symbol: {}", c as char)))
+    }
+
+    fn val_to_check(v: u8) -> char {
+        match v {
+            v if v < 32 => CROCKFORD[v as usize] as char,
+            v => CROCKFORD_CHECK[(v - 32) as usize] as char,
+        }
+    }
+}
+
+#[test]
+fn encode() {
+    use std::collections::HashMap;
+
+    // RFC 4648 section 10 test vectors, plus the other alphabets
+    let tests: HashMap<(&str, Base32Variant), Vec<u8>> = [
+        (("", Base32Variant::Standard), "".as_bytes().to_vec()),
+        (
+            ("MY======", Base32Variant::Standard),
+            "f".as_bytes().to_vec(),
+        ),
+        (
+            ("MZXQ====", Base32Variant::Standard),
+            "fo".as_bytes().to_vec(),
+        ),
+        (
+            ("MZXW6===", Base32Variant::Standard),
+            "foo".as_bytes().to_vec(),
+        ),
+        (
+            ("MZXW6YQ=", Base32Variant::Standard),
+            "foob".as_bytes().to_vec(),
+        ),
+        (
+            ("MZXW6YTB", Base32Variant::Standard),
+            "fooba".as_bytes().to_vec(),
+        ),
+        (
+            ("MZXW6YTBOI======", Base32Variant::Standard),
+            "foobar".as_bytes().to_vec(),
+        ),
+        (
+            ("CO======", Base32Variant::ExtendedHex),
+            "f".as_bytes().to_vec(),
+        ),
+        (
+            ("CPNMUOJ1E8======", Base32Variant::ExtendedHex),
+            "foobar".as_bytes().to_vec(),
+        ),
+        (
+            ("CSQPYRK1E8", Base32Variant::Crockford),
+            "foobar".as_bytes().to_vec(),
+        ),
+        (
+            ("CSQPYRK1E86", Base32Variant::CrockfordCheck),
+            "foobar".as_bytes().to_vec(),
+        ),
+        (
+            ("c3zs6aubqe", Base32Variant::ZBase32),
+            "foobar".as_bytes().to_vec(),
+        ),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    for ((expected, variant), bytes) in tests {
+        let codec = Base32Codec { variant };
+        assert_eq!(expected.as_bytes().to_vec(), codec.encode(bytes).unwrap());
+    }
+}
+
+#[test]
+fn decode() {
+    use std::collections::HashMap;
+
+    let tests: HashMap<(&str, Base32Variant), Result<Vec<u8>, Error>> = [
+        (
+            ("MZXW6YTBOI======", Base32Variant::Standard),
+            Ok("foobar".as_bytes().to_vec()),
+        ),
+        (
+            ("MZXW6YTBOI", Base32Variant::Standard),
+            Ok("foobar".as_bytes().to_vec()),
+        ),
+        (
+            ("MZXW 6YTB OI", Base32Variant::Standard),
+            Ok("foobar".as_bytes().to_vec()),
+        ),
+        (
+            ("CPNMUOJ1E8======", Base32Variant::ExtendedHex),
+            Ok("foobar".as_bytes().to_vec()),
+        ),
+        (
+            ("csqp-yrk1-e8", Base32Variant::Crockford),
+            Ok("foobar".as_bytes().to_vec()),
+        ),
+        (
+            ("CSQPYRKIE8", Base32Variant::Crockford),
+            Ok("foobar".as_bytes().to_vec()),
+        ),
+        (
+            ("CSQPYRK1E86", Base32Variant::CrockfordCheck),
+            Ok("foobar".as_bytes().to_vec()),
+        ),
+        (
+            ("c3zs6aubqe", Base32Variant::ZBase32),
+            Ok("foobar".as_bytes().to_vec()),
+        ),
+        (
+            ("mzxw6ytb", Base32Variant::Standard),
+            Err(Error::new("Invalid char: m".to_string())),
+        ),
+        (
+            ("MZXW6YT", Base32Variant::Standard),
+            Err(Error::new(
+                "Non-zero trailing bits in base32 string".to_string(),
+            )),
+        ),
+        (
+            ("MZX", Base32Variant::Standard),
+            Err(Error::new(
+                "Invalid number of characters for base32 string".to_string(),
+            )),
+        ),
+        (
+            ("MY=====", Base32Variant::Standard),
+            Err(Error::new("Invalid padding for base32 string".to_string())),
+        ),
+        (
+            ("M=Y=====", Base32Variant::Standard),
+            Err(Error::new("Non-tailing padding".to_string())),
+        ),
+        (
+            ("MZXW6YT1", Base32Variant::Standard),
+            Err(Error::new("Invalid char: 1".to_string())),
+        ),
+        (
+            ("CSQPYRK1E8R", Base32Variant::CrockfordCheck),
+            Err(Error::new(
+                "Crockford check symbol does not match".to_string(),
+            )),
+        ),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    for ((data, variant), expected) in tests {
+        let codec = Base32Codec { variant };
+        assert_eq!(expected, codec.decode(data.as_bytes().to_vec()));
+    }
+}
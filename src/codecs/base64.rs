@@ -1,23 +1,49 @@
-use super::codec::Codec;
+use super::codec::{Codec, Framing};
 use super::error::Error;
+use super::score;
 use crate::Format;
 
-pub struct Base64Codec {}
+const MIME_LINE_LENGTH: usize = 76;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Base64Variant {
+    // RFC 4648 section 4, with strict padding
+    Standard,
+    // RFC 4648 section 5, written without padding as in JWTs
+    UrlSafe,
+    // Standard alphabet, written without padding
+    Unpadded,
+    // RFC 2045, wrapped at 76 columns with CRLF
+    Mime,
+    // Accepts either alphabet, missing padding, whitespace and PEM armor
+    Lenient,
+}
+
+pub struct Base64Codec {
+    pub variant: Base64Variant,
+}
 
 impl Codec for Base64Codec {
     fn format(&self) -> Format {
-        Format::Base64
+        match self.variant {
+            Base64Variant::Standard => Format::Base64,
+            Base64Variant::UrlSafe => Format::Base64Url,
+            Base64Variant::Unpadded => Format::Base64Unpadded,
+            Base64Variant::Mime => Format::Base64Mime,
+            Base64Variant::Lenient => Format::Base64Lenient,
+        }
     }
 
     fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
-        if s.len() % 4 != 0 {
+        let s = self.normalize(s);
+        if !s.len().is_multiple_of(4) {
             Err(Error::new(
                 "Invalid number of characters for base64 string".to_string(),
             ))
         } else {
             let mut tail_pd = 0;
             s.into_iter()
-                .map(|c| match Self::char_to_val(c as char) {
+                .map(|c| match self.to_standard(c).and_then(Self::char_to_val) {
                     // If we have a valid character come after a padding, error out
                     // Otherwise just pass values along
                     Ok(Some(_)) if tail_pd > 0 => {
@@ -36,6 +62,7 @@ impl Codec for Base64Codec {
                     pass => pass,
                 })
                 .collect::<Result<Vec<Option<u8>>, Error>>()
+                .and_then(|bytes| self.check_trailing_bits(bytes))
                 .map(|bytes| {
                     bytes
                         .chunks(4)
@@ -63,8 +90,8 @@ impl Codec for Base64Codec {
         }
     }
 
-    fn encode(&self, data: Vec<u8>) -> Result<String, Error> {
-        Ok(data
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let encoded = data
             .chunks(3)
             .flat_map(|group| match group.len() {
                 1 => vec![
@@ -91,25 +118,141 @@ impl Codec for Base64Codec {
                 // chunks() is guaranteed to return between length 1 and 3
                 _ => panic!("Error while encoding base64"),
             })
-            .map(|ch| match ch {
-                Some(v) => Self::val_to_char(v),
-                None => '=',
+            .filter_map(|ch| match (ch, self.variant) {
+                (Some(v), Base64Variant::UrlSafe) => Some(match Self::val_to_char(v) {
+                    '+' => '-',
+                    '/' => '_',
+                    c => c,
+                }),
+                (Some(v), _) => Some(Self::val_to_char(v)),
+                (None, Base64Variant::UrlSafe) | (None, Base64Variant::Unpadded) => None,
+                (None, _) => Some('='),
             })
-            .collect())
+            .collect::<Vec<char>>();
+
+        if self.variant == Base64Variant::Mime {
+            Ok(encoded
+                .chunks(MIME_LINE_LENGTH)
+                .map(|line| line.iter().collect::<String>())
+                .collect::<Vec<String>>()
+                .join("\r\n")
+                .into_bytes())
+        } else {
+            Ok(encoded.into_iter().collect::<String>().into_bytes())
+        }
+    }
+
+    fn inferrable(&self) -> bool {
+        // Lenient decoding would accept most plain text
+        self.variant != Base64Variant::Lenient
+    }
+
+    fn framing(&self) -> Option<Framing<'_>> {
+        match self.variant {
+            // Armor lines can't be recognized a group at a time
+            Base64Variant::Lenient => None,
+            Base64Variant::Mime => Some(Framing {
+                symbols: 4,
+                bytes: MIME_LINE_LENGTH / 4 * 3,
+                ignored: |&c| c == b'\r' || c == b'\n',
+                padding: Some(b'='),
+                separator: "\r\n",
+            }),
+            _ => Some(Framing {
+                symbols: 4,
+                bytes: 3,
+                ignored: |_| false,
+                padding: Some(b'='),
+                separator: "",
+            }),
+        }
+    }
+
+    fn confidence(&self, s: &[u8], decoded: &[u8]) -> f64 {
+        // The base 64 alphabet covers most of what people type, so only
+        // padding really sets it apart
+        let fit = match self.variant {
+            _ if s.trim_ascii_end().ends_with(b"=") => score::BASE64_PADDED,
+            Base64Variant::Standard | Base64Variant::Mime => score::BASE64,
+            _ => score::BASE64_LOOSE,
+        };
+        score::weigh(fit, decoded)
     }
 }
 
 impl Base64Codec {
+    // Strips what the variant allows to be ignored, and restores missing padding
+    fn normalize(&self, s: Vec<u8>) -> Vec<u8> {
+        let mut s = match self.variant {
+            Base64Variant::Mime => s
+                .into_iter()
+                .filter(|&c| c != b'\r' && c != b'\n')
+                .collect(),
+            Base64Variant::Lenient => s
+                .split(|&c| c == b'\n')
+                .filter(|line| !line.trim_ascii_start().starts_with(b"-----"))
+                .flatten()
+                .filter(|c| !c.is_ascii_whitespace())
+                .cloned()
+                .collect(),
+            _ => s,
+        };
+        if matches!(
+            self.variant,
+            Base64Variant::UrlSafe | Base64Variant::Unpadded | Base64Variant::Lenient
+        ) {
+            match s.len() % 4 {
+                2 => s.extend_from_slice(b"=="),
+                3 => s.push(b'='),
+                _ => (),
+            }
+        }
+        s
+    }
+
+    // Maps the variant's alphabet onto the standard one
+    fn to_standard(&self, c: u8) -> Result<char, Error> {
+        match (self.variant, c) {
+            (Base64Variant::UrlSafe, b'+') | (Base64Variant::UrlSafe, b'/') => {
+                Err(Error::new(format!("Invalid char: {}", c as char)))
+            }
+            (Base64Variant::UrlSafe, b'-') | (Base64Variant::Lenient, b'-') => Ok('+'),
+            (Base64Variant::UrlSafe, b'_') | (Base64Variant::Lenient, b'_') => Ok('/'),
+            _ => Ok(c as char),
+        }
+    }
+
+    // Variants we infer without padding to go on must also have their unused
+    // bits zeroed, or ordinary words would be mistaken for them
+    fn check_trailing_bits(&self, bytes: Vec<Option<u8>>) -> Result<Vec<Option<u8>>, Error> {
+        let canonical = match bytes.len() {
+            0 => true,
+            n => match (self.variant, bytes[n - 3], bytes[n - 2], bytes[n - 1]) {
+                (Base64Variant::Standard, _, _, _) | (Base64Variant::Lenient, _, _, _) => true,
+                (_, Some(v1), None, None) => v1 & 0b00001111 == 0,
+                (_, _, Some(v2), None) => v2 & 0b00000011 == 0,
+                _ => true,
+            },
+        };
+        if canonical {
+            Ok(bytes)
+        } else {
+            Err(Error::new(
+                "Non-zero trailing bits in base64 string".to_string(),
+            ))
+        }
+    }
+
     pub fn char_to_val(c: char) -> Result<Option<u8>, Error> {
         if c == '=' {
             Ok(None)
         } else {
             Ok(Some(match c {
-                c if c >= 'A' && c <= 'Z' => c as u8 - 65,
-                c if c >= 'a' && c <= 'z' => c as u8 - 71,
-                c if c >= '0' && c <= '9' => c as u8 + 4,
-                c if c == '+' => 62,
-                c if c == '/' => 63,
+                'A'..='Z' => c as u8 - 65,
+                'a'..='z' => c as u8 - 71,
+                '0'..='9' => c as u8 + 4,
+                '+' => 62,
+                '/' => 63,
                 _ => return Err(Error::new(format!("Invalid char: {}", c))),
             }))
         }
@@ -117,11 +260,11 @@ impl Base64Codec {
 
     pub fn val_to_char(v: u8) -> char {
         (match v & 0b00111111 {
-            z if z <= 25 => 'A' as u8 + z,
-            z if z <= 51 => 'a' as u8 + (z - 26),
-            z if z <= 61 => '0' as u8 + (z - 52),
-            z if z == 62 => '+' as u8,
-            z if z == 63 => '/' as u8,
+            z if z <= 25 => b'A' + z,
+            z if z <= 51 => b'a' + (z - 26),
+            z if z <= 61 => b'0' + (z - 52),
+            62 => b'+',
+            63 => b'/',
             _ => panic!("Base64 character encoding was passed more than 6 bits"),
         }) as char
     }
@@ -151,9 +294,11 @@ fn encode() {
     .cloned()
     .collect();
 
-    let codec = Base64Codec {};
+    let codec = Base64Codec {
+        variant: Base64Variant::Standard,
+    };
     for (expected, bytes) in tests {
-        assert_eq!(expected, codec.encode(bytes).unwrap());
+        assert_eq!(expected.as_bytes().to_vec(), codec.encode(bytes).unwrap());
     }
 }
 
@@ -184,8 +329,72 @@ fn decode() {
     .cloned()
     .collect();
 
-    let codec = Base64Codec {};
+    let codec = Base64Codec {
+        variant: Base64Variant::Standard,
+    };
     for (bytes, expected) in tests {
         assert_eq!(expected, codec.decode(bytes));
     }
 }
+
+#[test]
+fn variants() {
+    let mime_lines = format!("{}\r\nYWFh", "YWFh".repeat(19));
+    let pem = "-----BEGIN DATA-----\naGVsbG8g\nd29ybGQ\n-----END DATA-----\n";
+
+    let tests: Vec<(Base64Variant, &str, Vec<u8>)> = vec![
+        (Base64Variant::UrlSafe, "-_-_", vec![0xfb, 0xff, 0xbf]),
+        (Base64Variant::UrlSafe, "-_8", vec![0xfb, 0xff]),
+        (Base64Variant::Unpadded, "+/8", vec![0xfb, 0xff]),
+        (Base64Variant::Unpadded, "Zg", "f".as_bytes().to_vec()),
+        (
+            Base64Variant::Mime,
+            &mime_lines,
+            "a".repeat(60).into_bytes(),
+        ),
+    ];
+    for (variant, encoded, bytes) in tests {
+        let codec = Base64Codec { variant };
+        assert_eq!(
+            encoded.as_bytes().to_vec(),
+            codec.encode(bytes.clone()).unwrap()
+        );
+        assert_eq!(Ok(bytes), codec.decode(encoded.as_bytes().to_vec()));
+    }
+
+    let tests = vec![
+        (
+            Base64Variant::UrlSafe,
+            "eyJhbGciOiJIUzI1NiJ9",
+            Ok("{\"alg\":\"HS256\"}".as_bytes().to_vec()),
+        ),
+        (Base64Variant::UrlSafe, "-_8=", Ok(vec![0xfb, 0xff])),
+        (
+            Base64Variant::UrlSafe,
+            "+/8",
+            Err(Error::new("Invalid char: +".to_string())),
+        ),
+        (
+            Base64Variant::Unpadded,
+            "Zh",
+            Err(Error::new(
+                "Non-zero trailing bits in base64 string".to_string(),
+            )),
+        ),
+        (
+            Base64Variant::Mime,
+            "aGVsbG8g\nd29ybGQ=\n",
+            Ok("hello world".as_bytes().to_vec()),
+        ),
+        (
+            Base64Variant::Lenient,
+            pem,
+            Ok("hello world".as_bytes().to_vec()),
+        ),
+        (Base64Variant::Lenient, " -_8 ", Ok(vec![0xfb, 0xff])),
+    ];
+    for (variant, encoded, expected) in tests {
+        let codec = Base64Codec { variant };
+        assert_eq!(expected, codec.decode(encoded.as_bytes().to_vec()));
+    }
+}
@@ -1,5 +1,6 @@
-use super::codec::Codec;
+use super::codec::{Codec, Framing};
 use super::error::Error;
+use super::score;
 use crate::Format;
 
 pub struct BinaryCodec {}
@@ -61,7 +62,7 @@ impl Codec for BinaryCodec {
         }
     }
 
-    fn encode(&self, data: Vec<u8>) -> Result<String, Error> {
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
         Ok(data
             .into_iter()
             .map(|byte| {
@@ -78,6 +79,21 @@ impl Codec for BinaryCodec {
                 )
             })
             .collect::<Vec<String>>()
-            .join(" "))
+            .join(" ")
+            .into_bytes())
+    }
+
+    fn framing(&self) -> Option<Framing<'_>> {
+        Some(Framing {
+            symbols: 8,
+            bytes: 1,
+            ignored: u8::is_ascii_whitespace,
+            padding: None,
+            separator: " ",
+        })
+    }
+
+    fn confidence(&self, _s: &[u8], decoded: &[u8]) -> f64 {
+        score::weigh(0.95, decoded)
     }
 }
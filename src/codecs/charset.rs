@@ -0,0 +1,375 @@
+use super::codec::Codec;
+use super::error::Error;
+use super::score;
+use crate::Format;
+
+mod tables;
+
+use tables::*;
+
+// A legacy charset with one byte per character
+pub struct Charset {
+    pub name: &'static str,
+    // The name used for this charset on the command line
+    pub arg: &'static str,
+    // The characters for bytes 0x80 to 0xff over ASCII, or for all 256
+    pub table: &'static [char],
+}
+
+// Roughly from most to least used, which settles ties when guessing. There's
+// no 8859-12; it was meant for Devanagari and abandoned.
+pub const CHARSETS: [Charset; 30] = [
+    Charset {
+        name: "windows-1252",
+        arg: "windows1252",
+        table: &WINDOWS_1252,
+    },
+    Charset {
+        name: "iso-8859-1",
+        arg: "iso8859-1",
+        table: &ISO_8859_1,
+    },
+    Charset {
+        name: "iso-8859-15",
+        arg: "iso8859-15",
+        table: &ISO_8859_15,
+    },
+    Charset {
+        name: "windows-1251",
+        arg: "windows1251",
+        table: &WINDOWS_1251,
+    },
+    Charset {
+        name: "koi8-r",
+        arg: "koi8r",
+        table: &KOI8_R,
+    },
+    Charset {
+        name: "koi8-u",
+        arg: "koi8u",
+        table: &KOI8_U,
+    },
+    Charset {
+        name: "iso-8859-5",
+        arg: "iso8859-5",
+        table: &ISO_8859_5,
+    },
+    Charset {
+        name: "windows-1250",
+        arg: "windows1250",
+        table: &WINDOWS_1250,
+    },
+    Charset {
+        name: "iso-8859-2",
+        arg: "iso8859-2",
+        table: &ISO_8859_2,
+    },
+    Charset {
+        name: "windows-1253",
+        arg: "windows1253",
+        table: &WINDOWS_1253,
+    },
+    Charset {
+        name: "iso-8859-7",
+        arg: "iso8859-7",
+        table: &ISO_8859_7,
+    },
+    Charset {
+        name: "windows-1254",
+        arg: "windows1254",
+        table: &WINDOWS_1254,
+    },
+    Charset {
+        name: "iso-8859-9",
+        arg: "iso8859-9",
+        table: &ISO_8859_9,
+    },
+    Charset {
+        name: "windows-1255",
+        arg: "windows1255",
+        table: &WINDOWS_1255,
+    },
+    Charset {
+        name: "iso-8859-8",
+        arg: "iso8859-8",
+        table: &ISO_8859_8,
+    },
+    Charset {
+        name: "windows-1256",
+        arg: "windows1256",
+        table: &WINDOWS_1256,
+    },
+    Charset {
+        name: "iso-8859-6",
+        arg: "iso8859-6",
+        table: &ISO_8859_6,
+    },
+    Charset {
+        name: "windows-1257",
+        arg: "windows1257",
+        table: &WINDOWS_1257,
+    },
+    Charset {
+        name: "iso-8859-13",
+        arg: "iso8859-13",
+        table: &ISO_8859_13,
+    },
+    Charset {
+        name: "windows-1258",
+        arg: "windows1258",
+        table: &WINDOWS_1258,
+    },
+    Charset {
+        name: "iso-8859-3",
+        arg: "iso8859-3",
+        table: &ISO_8859_3,
+    },
+    Charset {
+        name: "iso-8859-4",
+        arg: "iso8859-4",
+        table: &ISO_8859_4,
+    },
+    Charset {
+        name: "iso-8859-10",
+        arg: "iso8859-10",
+        table: &ISO_8859_10,
+    },
+    Charset {
+        name: "iso-8859-11",
+        arg: "iso8859-11",
+        table: &ISO_8859_11,
+    },
+    Charset {
+        name: "iso-8859-14",
+        arg: "iso8859-14",
+        table: &ISO_8859_14,
+    },
+    Charset {
+        name: "iso-8859-16",
+        arg: "iso8859-16",
+        table: &ISO_8859_16,
+    },
+    Charset {
+        name: "mac roman",
+        arg: "macroman",
+        table: &MAC_ROMAN,
+    },
+    Charset {
+        name: "ebcdic 037",
+        arg: "ebcdic037",
+        table: &EBCDIC_037,
+    },
+    Charset {
+        name: "ebcdic 500",
+        arg: "ebcdic500",
+        table: &EBCDIC_500,
+    },
+    Charset {
+        name: "ebcdic 1047",
+        arg: "ebcdic1047",
+        table: &EBCDIC_1047,
+    },
+];
+
+// The commonest letters in Latin, Cyrillic, Greek, Hebrew and Arabic text.
+// Whatever the language, over half of the letters in a text are usually
+// among these, and text decoded with the wrong charset has far fewer.
+// Accented Latin letters count for half, being common in some languages.
+const COMMON_LETTERS: [&str; 5] = [
+    "eaiotnsrlhdcu",
+    "оеаинтсрвлкмдпу",
+    "αοιετσνηυρκπμλάέίό",
+    "יוהלארמתבנשעםן",
+    "اليمونهربتعفد",
+];
+
+// Text in a single-byte charset, decoded to utf8 and encoded from it
+pub struct CharsetCodec {
+    // Index into CHARSETS
+    pub charset: u8,
+}
+
+impl Codec for CharsetCodec {
+    fn format(&self) -> Format {
+        Format::Charset(self.charset)
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        s.iter()
+            .enumerate()
+            .map(|(i, &b)| match self.char(b) {
+                UNDEFINED => Err(Error::at(
+                    format!("{:#04x} isn't a character in {}", b, self.format()),
+                    i,
+                )),
+                c => Ok(c),
+            })
+            .collect::<Result<String, Error>>()
+            .map(String::into_bytes)
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let s = String::from_utf8(data)
+            .map_err(|e| Error::at("invalid utf8".to_string(), e.utf8_error().valid_up_to()))?;
+        s.char_indices()
+            .map(|(i, c)| {
+                self.byte(c)
+                    .ok_or_else(|| Error::at(format!("{} has no {:?}", self.format(), c), i))
+            })
+            .collect()
+    }
+
+    fn printable(&self) -> bool {
+        false
+    }
+
+    // Every charset decodes nearly anything, so this has to tell which one
+    // was meant from how the text reads. Text doesn't have control
+    // characters or many symbols, words stick to one script and don't change
+    // case halfway, and most letters are among the commonest of their script.
+    fn confidence(&self, s: &[u8], decoded: &[u8]) -> f64 {
+        let text = String::from_utf8_lossy(decoded);
+        if text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+        {
+            return 0.0;
+        }
+        let mut letters = 0;
+        let mut odd = 0;
+        let mut common = 0.0;
+        // Symbols that aren't punctuation stay part of the word they're in
+        for word in text.split(|c: char| !c.is_alphabetic() && textual(c)) {
+            let chars = word.chars().collect::<Vec<_>>();
+            let latin = chars.iter().any(|c| c.is_ascii());
+            letters += chars.len();
+            common += chars
+                .iter()
+                .flat_map(|c| c.to_lowercase())
+                .map(|c| match c {
+                    c if COMMON_LETTERS.iter().any(|common| common.contains(c)) => 1.0,
+                    '\u{c0}'..='\u{24f}' => 0.5,
+                    _ => 0.0,
+                })
+                .sum::<f64>();
+            // A word that mixes Latin with another script or symbols is all
+            // wrong
+            if chars.iter().any(|&c| !c.is_alphabetic())
+                || (latin && chars.iter().any(|&c| c >= '\u{250}'))
+            {
+                odd += chars.len();
+                continue;
+            }
+            odd += chars
+                .windows(2)
+                .filter(|pair| pair[0].is_lowercase() && pair[1].is_uppercase())
+                .count();
+        }
+        let fit = if letters == 0 {
+            1.0
+        } else {
+            (letters - odd) as f64 / letters as f64 * common / letters as f64
+        };
+        if s == decoded {
+            // Plain ASCII reads the same in utf8, which should win
+            return score::weigh(0.0, decoded) * fit;
+        }
+        // Not score::readability, which would favour scripts with lowercase
+        let chars = text.chars().count();
+        let textual = text.chars().filter(|&c| textual(c)).count();
+        0.5 * fit * textual as f64 / chars as f64
+    }
+}
+
+// Letters, digits, spacing and punctuation, but not other symbols
+fn textual(c: char) -> bool {
+    c.is_alphabetic()
+        || c.is_ascii_digit()
+        || c.is_whitespace()
+        || c.is_ascii_punctuation()
+        // Arabic punctuation, then general punctuation and currency signs
+        || matches!(c, '«' | '»' | '¡' | '¿' | '،' | '؛' | '؟' | '\u{2010}'..='\u{20cf}')
+}
+
+impl CharsetCodec {
+    fn table(&self) -> &'static [char] {
+        CHARSETS[self.charset as usize].table
+    }
+
+    fn char(&self, b: u8) -> char {
+        match self.table() {
+            table if table.len() == 256 => table[b as usize],
+            _ if b < 0x80 => b as char,
+            table => table[b as usize - 0x80],
+        }
+    }
+
+    fn byte(&self, c: char) -> Option<u8> {
+        let table = self.table();
+        if table.len() < 256 && c.is_ascii() {
+            return Some(c as u8);
+        }
+        let offset = 256 - table.len();
+        table
+            .iter()
+            .position(|&t| t == c && t != UNDEFINED)
+            .map(|i| (i + offset) as u8)
+    }
+}
+
+#[test]
+fn encode() {
+    let charset = |arg: &str| CHARSETS.iter().position(|c| c.arg == arg).unwrap() as u8;
+    let tests = vec![
+        ("iso8859-1", "café", vec![0x63, 0x61, 0x66, 0xe9]),
+        ("windows1252", "“€5”", vec![0x93, 0x80, 0x35, 0x94]),
+        (
+            "windows1251",
+            "Привет",
+            vec![0xcf, 0xf0, 0xe8, 0xe2, 0xe5, 0xf2],
+        ),
+        ("koi8r", "Привет", vec![0xf0, 0xd2, 0xc9, 0xd7, 0xc5, 0xd4]),
+        ("iso8859-7", "Ωμέγα", vec![0xd9, 0xec, 0xdd, 0xe3, 0xe1]),
+        ("macroman", "Ä†", vec![0x80, 0xa0]),
+        (
+            "ebcdic037",
+            "Hi [1]",
+            vec![0xc8, 0x89, 0x40, 0xba, 0xf1, 0xbb],
+        ),
+        (
+            "ebcdic1047",
+            "Hi [1]",
+            vec![0xc8, 0x89, 0x40, 0xad, 0xf1, 0xbd],
+        ),
+        ("ebcdic500", "Hi!", vec![0xc8, 0x89, 0x4f]),
+    ];
+
+    for (arg, text, encoded) in tests {
+        let codec = CharsetCodec {
+            charset: charset(arg),
+        };
+        assert_eq!(encoded, codec.encode(text.as_bytes().to_vec()).unwrap());
+        assert_eq!(text.as_bytes().to_vec(), codec.decode(encoded).unwrap());
+    }
+
+    let latin1 = CharsetCodec {
+        charset: charset("iso8859-1"),
+    };
+    assert_eq!(
+        "iso-8859-1 has no '€' at byte 2",
+        latin1
+            .encode("5 €".as_bytes().to_vec())
+            .unwrap_err()
+            .to_string()
+    );
+    let windows1252 = CharsetCodec {
+        charset: charset("windows1252"),
+    };
+    assert_eq!(
+        "0x81 isn't a character in windows-1252 at byte 1",
+        windows1252
+            .decode(vec![0x61, 0x81])
+            .unwrap_err()
+            .to_string()
+    );
+}
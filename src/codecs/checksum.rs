@@ -0,0 +1,425 @@
+use super::codec::Codec;
+use super::error::Error;
+use crate::Format;
+
+// A CRC in the usual width/poly/init/refin/refout/xorout terms
+pub struct Crc {
+    pub name: &'static str,
+    // The name used for this CRC on the command line
+    pub arg: &'static str,
+    pub width: u32,
+    pub poly: u64,
+    pub init: u64,
+    // Whether each input byte is taken least significant bit first
+    pub refin: bool,
+    // Whether the final register is reflected before xorout
+    pub refout: bool,
+    pub xorout: u64,
+    // The CRC of "123456789"
+    pub check: u64,
+}
+
+pub const CATALOG: [Crc; 14] = [
+    Crc {
+        name: "crc-8",
+        arg: "crc8",
+        width: 8,
+        poly: 0x07,
+        init: 0x00,
+        refin: false,
+        refout: false,
+        xorout: 0x00,
+        check: 0xf4,
+    },
+    Crc {
+        name: "crc-8/maxim",
+        arg: "crc8-maxim",
+        width: 8,
+        poly: 0x31,
+        init: 0x00,
+        refin: true,
+        refout: true,
+        xorout: 0x00,
+        check: 0xa1,
+    },
+    Crc {
+        name: "crc-16/arc",
+        arg: "crc16-arc",
+        width: 16,
+        poly: 0x8005,
+        init: 0x0000,
+        refin: true,
+        refout: true,
+        xorout: 0x0000,
+        check: 0xbb3d,
+    },
+    Crc {
+        name: "crc-16/ccitt-false",
+        arg: "crc16-ccitt-false",
+        width: 16,
+        poly: 0x1021,
+        init: 0xffff,
+        refin: false,
+        refout: false,
+        xorout: 0x0000,
+        check: 0x29b1,
+    },
+    Crc {
+        name: "crc-16/xmodem",
+        arg: "crc16-xmodem",
+        width: 16,
+        poly: 0x1021,
+        init: 0x0000,
+        refin: false,
+        refout: false,
+        xorout: 0x0000,
+        check: 0x31c3,
+    },
+    Crc {
+        name: "crc-16/kermit",
+        arg: "crc16-kermit",
+        width: 16,
+        poly: 0x1021,
+        init: 0x0000,
+        refin: true,
+        refout: true,
+        xorout: 0x0000,
+        check: 0x2189,
+    },
+    Crc {
+        name: "crc-16/modbus",
+        arg: "crc16-modbus",
+        width: 16,
+        poly: 0x8005,
+        init: 0xffff,
+        refin: true,
+        refout: true,
+        xorout: 0x0000,
+        check: 0x4b37,
+    },
+    Crc {
+        name: "crc-32",
+        arg: "crc32",
+        width: 32,
+        poly: 0x04c11db7,
+        init: 0xffffffff,
+        refin: true,
+        refout: true,
+        xorout: 0xffffffff,
+        check: 0xcbf43926,
+    },
+    Crc {
+        name: "crc-32c",
+        arg: "crc32c",
+        width: 32,
+        poly: 0x1edc6f41,
+        init: 0xffffffff,
+        refin: true,
+        refout: true,
+        xorout: 0xffffffff,
+        check: 0xe3069283,
+    },
+    Crc {
+        name: "crc-32/bzip2",
+        arg: "crc32-bzip2",
+        width: 32,
+        poly: 0x04c11db7,
+        init: 0xffffffff,
+        refin: false,
+        refout: false,
+        xorout: 0xffffffff,
+        check: 0xfc891918,
+    },
+    Crc {
+        name: "crc-32/mpeg-2",
+        arg: "crc32-mpeg2",
+        width: 32,
+        poly: 0x04c11db7,
+        init: 0xffffffff,
+        refin: false,
+        refout: false,
+        xorout: 0x00000000,
+        check: 0x0376e6e7,
+    },
+    Crc {
+        name: "crc-64/ecma-182",
+        arg: "crc64-ecma",
+        width: 64,
+        poly: 0x42f0e1eba9ea3693,
+        init: 0x0000000000000000,
+        refin: false,
+        refout: false,
+        xorout: 0x0000000000000000,
+        check: 0x6c40df5f0b497347,
+    },
+    Crc {
+        name: "crc-64/xz",
+        arg: "crc64-xz",
+        width: 64,
+        poly: 0x42f0e1eba9ea3693,
+        init: 0xffffffffffffffff,
+        refin: true,
+        refout: true,
+        xorout: 0xffffffffffffffff,
+        check: 0x995dc9bbdf1939fa,
+    },
+    Crc {
+        name: "crc-64/go-iso",
+        arg: "crc64-go",
+        width: 64,
+        poly: 0x000000000000001b,
+        init: 0xffffffffffffffff,
+        refin: true,
+        refout: true,
+        xorout: 0xffffffffffffffff,
+        check: 0xb90956c775a41001,
+    },
+];
+
+// Verhoeff's multiplication table for the dihedral group D5
+const VERHOEFF_D: [[u8; 10]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+    [1, 2, 3, 4, 0, 6, 7, 8, 9, 5],
+    [2, 3, 4, 0, 1, 7, 8, 9, 5, 6],
+    [3, 4, 0, 1, 2, 8, 9, 5, 6, 7],
+    [4, 0, 1, 2, 3, 9, 5, 6, 7, 8],
+    [5, 9, 8, 7, 6, 0, 4, 3, 2, 1],
+    [6, 5, 9, 8, 7, 1, 0, 4, 3, 2],
+    [7, 6, 5, 9, 8, 2, 1, 0, 4, 3],
+    [8, 7, 6, 5, 9, 3, 2, 1, 0, 4],
+    [9, 8, 7, 6, 5, 4, 3, 2, 1, 0],
+];
+// Verhoeff's permutation, applied once more for each position
+const VERHOEFF_P: [[u8; 10]; 8] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+    [1, 5, 7, 6, 2, 8, 3, 0, 9, 4],
+    [5, 8, 0, 3, 7, 9, 6, 1, 4, 2],
+    [8, 9, 1, 6, 0, 4, 3, 5, 2, 7],
+    [9, 4, 5, 3, 1, 2, 6, 8, 7, 0],
+    [4, 2, 8, 6, 5, 7, 3, 9, 0, 1],
+    [2, 7, 9, 3, 8, 0, 6, 4, 1, 5],
+    [7, 0, 4, 6, 9, 1, 3, 2, 5, 8],
+];
+const VERHOEFF_INV: [u8; 10] = [0, 4, 3, 2, 1, 5, 6, 7, 8, 9];
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum ChecksumKind {
+    // Index into CATALOG
+    Crc(u8),
+    Adler32,
+    Fletcher16,
+    // Over little-endian 16-bit words, with odd input padded by a zero byte
+    Fletcher32,
+    // Check digits for a string of decimal digits
+    Luhn,
+    Verhoeff,
+}
+
+pub struct ChecksumCodec {
+    pub kind: ChecksumKind,
+}
+
+impl Codec for ChecksumCodec {
+    fn format(&self) -> Format {
+        match self.kind {
+            ChecksumKind::Crc(i) => Format::Crc(i),
+            ChecksumKind::Adler32 => Format::Adler32,
+            ChecksumKind::Fletcher16 => Format::Fletcher16,
+            ChecksumKind::Fletcher32 => Format::Fletcher32,
+            ChecksumKind::Luhn => Format::Luhn,
+            ChecksumKind::Verhoeff => Format::Verhoeff,
+        }
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let (value, width) = match self.kind {
+            ChecksumKind::Crc(i) => {
+                let crc = &CATALOG[i as usize];
+                (crc.checksum(&data), crc.width)
+            }
+            ChecksumKind::Adler32 => (adler32(&data), 32),
+            ChecksumKind::Fletcher16 => (fletcher16(&data), 16),
+            ChecksumKind::Fletcher32 => (fletcher32(&data), 32),
+            ChecksumKind::Luhn => return self.check_digit(data, luhn),
+            ChecksumKind::Verhoeff => return self.check_digit(data, verhoeff),
+        };
+        Ok(format!("{:0width$x}", value, width = width as usize / 4).into_bytes())
+    }
+
+    fn decodable(&self) -> bool {
+        false
+    }
+}
+
+impl ChecksumCodec {
+    // Computes the check digit for decimal digits, ignoring whitespace
+    fn check_digit(&self, data: Vec<u8>, f: fn(&[u8]) -> u8) -> Result<Vec<u8>, Error> {
+        let digits = data
+            .into_iter()
+            .enumerate()
+            .filter(|(_, c)| !c.is_ascii_whitespace())
+            .map(|(i, c)| match c {
+                b'0'..=b'9' => Ok(c - b'0'),
+                c => Err(Error::at(
+                    format!("Invalid digit {} for {}", c as char, self.format()),
+                    i,
+                )),
+            })
+            .collect::<Result<Vec<u8>, Error>>()?;
+        if digits.is_empty() {
+            return Err(Error::new("Empty number".to_string()));
+        }
+        Ok(vec![b'0' + f(&digits)])
+    }
+}
+
+impl Crc {
+    pub fn checksum(&self, data: &[u8]) -> u64 {
+        let top = 1 << (self.width - 1);
+        let mask = u64::MAX >> (64 - self.width);
+        let mut crc = self.init;
+        for &byte in data {
+            let byte = if self.refin {
+                byte.reverse_bits()
+            } else {
+                byte
+            };
+            crc ^= (byte as u64) << (self.width - 8);
+            for _ in 0..8 {
+                crc = if crc & top != 0 {
+                    (crc << 1) ^ self.poly
+                } else {
+                    crc << 1
+                };
+            }
+            crc &= mask;
+        }
+        if self.refout {
+            crc = crc.reverse_bits() >> (64 - self.width);
+        }
+        (crc ^ self.xorout) & mask
+    }
+}
+
+// Catalogued CRCs of data that come to expected, and whether they only
+// match once their bytes are swapped, as when a frame stores them
+// little-endian
+pub fn matching(data: &[u8], expected: u64) -> Vec<(&'static Crc, bool)> {
+    CATALOG
+        .iter()
+        .filter_map(|crc| {
+            let value = crc.checksum(data);
+            if value == expected {
+                Some((crc, false))
+            } else if value.swap_bytes() >> (64 - crc.width) == expected {
+                Some((crc, true))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+pub fn adler32(data: &[u8]) -> u64 {
+    let (a, b) = data.iter().fold((1u64, 0u64), |(a, b), &byte| {
+        let a = (a + byte as u64) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+pub fn fletcher16(data: &[u8]) -> u64 {
+    let (a, b) = data.iter().fold((0u64, 0u64), |(a, b), &byte| {
+        let a = (a + byte as u64) % 255;
+        (a, (b + a) % 255)
+    });
+    (b << 8) | a
+}
+
+pub fn fletcher32(data: &[u8]) -> u64 {
+    let (a, b) = data.chunks(2).fold((0u64, 0u64), |(a, b), word| {
+        let word = word[0] as u64 | (*word.get(1).unwrap_or(&0) as u64) << 8;
+        let a = (a + word) % 65535;
+        (a, (b + a) % 65535)
+    });
+    (b << 16) | a
+}
+
+fn luhn(digits: &[u8]) -> u8 {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2 == 0, d as u32 * 2) {
+            (true, double) if double > 9 => double - 9,
+            (true, double) => double,
+            (false, _) => d as u32,
+        })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+fn verhoeff(digits: &[u8]) -> u8 {
+    let c = digits.iter().rev().enumerate().fold(0, |c, (i, &d)| {
+        VERHOEFF_D[c as usize][VERHOEFF_P[(i + 1) % 8][d as usize] as usize]
+    });
+    VERHOEFF_INV[c as usize]
+}
+
+#[test]
+fn catalog() {
+    for crc in CATALOG.iter() {
+        assert_eq!(crc.check, crc.checksum(b"123456789"), "{}", crc.name);
+    }
+}
+
+#[test]
+fn encode() {
+    use std::collections::HashMap;
+
+    let tests: HashMap<(ChecksumKind, &str), &str> = [
+        ((ChecksumKind::Crc(7), "123456789"), "cbf43926"),
+        ((ChecksumKind::Crc(0), ""), "00"),
+        ((ChecksumKind::Adler32, "Wikipedia"), "11e60398"),
+        ((ChecksumKind::Fletcher16, "abcde"), "c8f0"),
+        ((ChecksumKind::Fletcher32, "abcde"), "f04fc729"),
+        ((ChecksumKind::Fletcher32, "abcdef"), "56502d2a"),
+        ((ChecksumKind::Luhn, "7992739871"), "3"),
+        ((ChecksumKind::Luhn, "4111 1111 1111 111"), "1"),
+        ((ChecksumKind::Verhoeff, "236"), "3"),
+        ((ChecksumKind::Verhoeff, "12345"), "1"),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    for ((kind, data), expected) in tests {
+        let codec = ChecksumCodec { kind };
+        assert_eq!(
+            expected.as_bytes().to_vec(),
+            codec.encode(data.as_bytes().to_vec()).unwrap()
+        );
+    }
+
+    let codec = ChecksumCodec {
+        kind: ChecksumKind::Luhn,
+    };
+    assert_eq!(
+        Err(Error::at("Invalid digit x for luhn".to_string(), 2)),
+        codec.encode(b"12x4".to_vec())
+    );
+}
+
+#[test]
+fn matches() {
+    let names = |data: &[u8], expected| {
+        matching(data, expected)
+            .into_iter()
+            .map(|(crc, swapped)| (crc.arg, swapped))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(vec![("crc32", false)], names(b"123456789", 0xcbf43926));
+    // As the bytes would sit in a little-endian frame
+    assert_eq!(vec![("crc32", true)], names(b"123456789", 0x2639f4cb));
+    assert_eq!(Vec::<(&str, bool)>::new(), names(b"123456789", 0x12345678));
+}
@@ -0,0 +1,226 @@
+use super::codec::Codec;
+use super::error::Error;
+use crate::Format;
+
+// Classical ciphers over the 26 letters of the English alphabet. Case is
+// kept, and anything that isn't an ASCII letter passes through untouched.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Cipher {
+    Rot13,
+    Caesar(u8),
+    Atbash,
+    // a and b in a * x + b, where a must be coprime with 26
+    Affine(Option<(u8, u8)>),
+    // Ciphers with a key leave it unset until one is given
+    Vigenere(Option<Vec<u8>>),
+    Beaufort(Option<Vec<u8>>),
+    // The letters a to z are replaced by these, in order
+    Substitution(Option<Vec<u8>>),
+}
+
+pub struct CipherCodec {
+    pub cipher: Cipher,
+}
+
+impl Codec for CipherCodec {
+    fn format(&self) -> Format {
+        match self.cipher {
+            Cipher::Rot13 => Format::Rot13,
+            Cipher::Caesar(_) => Format::Caesar,
+            Cipher::Atbash => Format::Atbash,
+            Cipher::Affine(_) => Format::Affine,
+            Cipher::Vigenere(_) => Format::Vigenere,
+            Cipher::Beaufort(_) => Format::Beaufort,
+            Cipher::Substitution(_) => Format::Substitution,
+        }
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        self.apply(s, true)
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        self.apply(data, false)
+    }
+
+    fn inferrable(&self) -> bool {
+        false
+    }
+}
+
+impl CipherCodec {
+    fn apply(&self, data: Vec<u8>, decoding: bool) -> Result<Vec<u8>, Error> {
+        if std::str::from_utf8(&data).is_err() {
+            return Err(Error::new("input data is not utf8".to_string()));
+        }
+        let letter = self.letter(decoding)?;
+        // Letters are always single bytes in utf8, and never part of a
+        // longer character, so they can be swapped in place
+        let mut n = 0;
+        Ok(data
+            .into_iter()
+            .map(|c| {
+                let base = match c {
+                    b'a'..=b'z' => b'a',
+                    b'A'..=b'Z' => b'A',
+                    _ => return c,
+                };
+                let out = base + letter(c - base, n);
+                n += 1;
+                out
+            })
+            .collect())
+    }
+
+    // What each letter, from 0 to 25, becomes given how many letters came
+    // before it
+    fn letter(&self, decoding: bool) -> Result<Box<dyn Fn(u8, usize) -> u8>, Error> {
+        let shift = |by: u8| -> Box<dyn Fn(u8, usize) -> u8> {
+            let by = if decoding { 26 - by % 26 } else { by % 26 };
+            Box::new(move |x, _| (x + by) % 26)
+        };
+        Ok(match &self.cipher {
+            Cipher::Rot13 => shift(13),
+            Cipher::Caesar(by) => shift(*by),
+            Cipher::Atbash => Box::new(|x, _| 25 - x),
+            Cipher::Affine(params) => {
+                let (a, b) = params.ok_or_else(|| {
+                    Error::new(format!(
+                        "No parameters given for {}; use --affine",
+                        self.format()
+                    ))
+                })?;
+                let inverse = (1..26u8)
+                    .find(|i| (a as u32 * *i as u32) % 26 == 1)
+                    .ok_or_else(|| {
+                        Error::new(format!(
+                            "{} a must be coprime with 26, not {}",
+                            self.format(),
+                            a
+                        ))
+                    })?;
+                if decoding {
+                    Box::new(move |x, _| ((inverse as u32 * (x + 26 - b) as u32) % 26) as u8)
+                } else {
+                    Box::new(move |x, _| ((a as u32 * x as u32 + b as u32) % 26) as u8)
+                }
+            }
+            Cipher::Vigenere(key) => {
+                let key = self.shifts(key)?;
+                if decoding {
+                    Box::new(move |x, n| (x + 26 - key[n % key.len()]) % 26)
+                } else {
+                    Box::new(move |x, n| (x + key[n % key.len()]) % 26)
+                }
+            }
+            // Beaufort is its own inverse
+            Cipher::Beaufort(key) => {
+                let key = self.shifts(key)?;
+                Box::new(move |x, n| (key[n % key.len()] + 26 - x) % 26)
+            }
+            Cipher::Substitution(key) => {
+                let key = self.shifts(key)?;
+                let mut sorted = key.clone();
+                sorted.sort_unstable();
+                if sorted != (0..26).collect::<Vec<u8>>() {
+                    return Err(Error::new(format!(
+                        "{} key must have each of the 26 letters once",
+                        self.format()
+                    )));
+                }
+                if decoding {
+                    let mut inverse = [0; 26];
+                    key.iter()
+                        .enumerate()
+                        .for_each(|(i, &k)| inverse[k as usize] = i as u8);
+                    Box::new(move |x, _| inverse[x as usize])
+                } else {
+                    Box::new(move |x, _| key[x as usize])
+                }
+            }
+        })
+    }
+
+    // The key's letters as numbers from 0 to 25
+    fn shifts(&self, key: &Option<Vec<u8>>) -> Result<Vec<u8>, Error> {
+        let key = key
+            .as_ref()
+            .ok_or_else(|| Error::new(format!("No key given for {}; use --key", self.format())))?;
+        if key.is_empty() {
+            return Err(Error::new(format!("{} key is empty", self.format())));
+        }
+        key.iter()
+            .enumerate()
+            .map(|(i, &k)| match k {
+                b'a'..=b'z' => Ok(k - b'a'),
+                b'A'..=b'Z' => Ok(k - b'A'),
+                _ => Err(Error::at(
+                    format!("{} key can only have letters", self.format()),
+                    i,
+                )),
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn encode() {
+    let key = |k: &str| Some(k.as_bytes().to_vec());
+    let tests = vec![
+        (Cipher::Rot13, "Hello, World!", "Uryyb, Jbeyq!"),
+        (Cipher::Caesar(3), "Hello, World!", "Khoor, Zruog!"),
+        (Cipher::Caesar(29), "Hello, World!", "Khoor, Zruog!"),
+        (Cipher::Atbash, "Hello, World!", "Svool, Dliow!"),
+        (
+            Cipher::Affine(Some((5, 8))),
+            "AFFINE cipher",
+            "IHHWVC swfrcp",
+        ),
+        (
+            Cipher::Vigenere(key("LEMON")),
+            "Attack at dawn!",
+            "Lxfopv ef rnhr!",
+        ),
+        (
+            Cipher::Vigenere(key("lemon")),
+            "ATTACKATDAWN",
+            "LXFOPVEFRNHR",
+        ),
+        (
+            Cipher::Beaufort(key("LEMON")),
+            "Attack at dawn!",
+            "Lltolb et lnpr!",
+        ),
+        (
+            Cipher::Substitution(key("ZEBRASCDFGHIJKLMNOPQTUVWXY")),
+            "flee at once. we are discovered!",
+            "siaa zq lkba. va zoa rfpbluaoar!",
+        ),
+        // Anything past ASCII is left alone
+        (Cipher::Caesar(1), "Zürich", "Aüsjdi"),
+    ];
+
+    for (cipher, plain, encoded) in tests {
+        let codec = CipherCodec { cipher };
+        assert_eq!(
+            encoded.as_bytes().to_vec(),
+            codec.encode(plain.as_bytes().to_vec()).unwrap()
+        );
+        assert_eq!(
+            plain.as_bytes().to_vec(),
+            codec.decode(encoded.as_bytes().to_vec()).unwrap()
+        );
+    }
+
+    let errors = vec![
+        Cipher::Vigenere(None),
+        Cipher::Vigenere(key("two words")),
+        Cipher::Affine(None),
+        Cipher::Affine(Some((13, 1))),
+        Cipher::Substitution(key("ABC")),
+        Cipher::Substitution(key("AACDEFGHIJKLMNOPQRSTUVWXYZ")),
+    ];
+    for cipher in errors {
+        assert!(CipherCodec { cipher }.encode(b"abc".to_vec()).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use super::codec::Codec;
+use super::error::Error;
+use crate::Format;
+use encoding_rs::{
+    DecoderResult, EncoderResult, Encoding, BIG5, EUC_JP, EUC_KR, GB18030, GBK, ISO_2022_JP,
+    SHIFT_JIS,
+};
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum CjkEncoding {
+    ShiftJis,
+    EucJp,
+    Iso2022Jp,
+    Gbk,
+    Gb18030,
+    Big5,
+    EucKr,
+}
+
+impl CjkEncoding {
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::ShiftJis,
+            Self::EucJp,
+            Self::Iso2022Jp,
+            Self::Gbk,
+            Self::Gb18030,
+            Self::Big5,
+            Self::EucKr,
+        ]
+    }
+}
+
+// Multi-byte legacy encodings for Chinese, Japanese and Korean text, decoded
+// to utf8 and encoded from it. Nearly any bytes are valid in some of them,
+// so they're never inferred.
+pub struct CjkCodec {
+    pub encoding: CjkEncoding,
+    // Whether decoding puts U+FFFD in place of invalid sequences rather
+    // than failing
+    pub replace: bool,
+}
+
+impl Codec for CjkCodec {
+    fn format(&self) -> Format {
+        match self.encoding {
+            CjkEncoding::ShiftJis => Format::ShiftJis,
+            CjkEncoding::EucJp => Format::EucJp,
+            CjkEncoding::Iso2022Jp => Format::Iso2022Jp,
+            CjkEncoding::Gbk => Format::Gbk,
+            CjkEncoding::Gb18030 => Format::Gb18030,
+            CjkEncoding::Big5 => Format::Big5,
+            CjkEncoding::EucKr => Format::EucKr,
+        }
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let mut decoder = self.encoding().new_decoder_without_bom_handling();
+        let mut out = String::with_capacity(
+            decoder
+                .max_utf8_buffer_length_without_replacement(s.len())
+                .unwrap_or(s.len()),
+        );
+        let mut read = 0;
+        loop {
+            let (result, n) =
+                decoder.decode_to_string_without_replacement(&s[read..], &mut out, true);
+            read += n;
+            match result {
+                DecoderResult::InputEmpty => return Ok(out.into_bytes()),
+                DecoderResult::OutputFull => out.reserve(s.len() - read + 16),
+                DecoderResult::Malformed(..) if self.replace => out.push('\u{fffd}'),
+                // The bad bytes end just before the ones read past them
+                DecoderResult::Malformed(bad, past) => {
+                    return Err(Error::at(
+                        format!("Invalid {} sequence", self.format()),
+                        read - bad as usize - past as usize,
+                    ))
+                }
+            }
+        }
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let s = String::from_utf8(data)
+            .map_err(|e| Error::at("invalid utf8".to_string(), e.utf8_error().valid_up_to()))?;
+        let mut encoder = self.encoding().new_encoder();
+        let mut out = Vec::with_capacity(
+            encoder
+                .max_buffer_length_from_utf8_without_replacement(s.len())
+                .unwrap_or(s.len()),
+        );
+        let mut read = 0;
+        loop {
+            let (result, n) =
+                encoder.encode_from_utf8_to_vec_without_replacement(&s[read..], &mut out, true);
+            read += n;
+            match result {
+                EncoderResult::InputEmpty => return Ok(out),
+                EncoderResult::OutputFull => out.reserve(s.len() - read + 16),
+                EncoderResult::Unmappable(c) => {
+                    return Err(Error::at(
+                        format!("{} has no {:?}", self.format(), c),
+                        read - c.len_utf8(),
+                    ))
+                }
+            }
+        }
+    }
+
+    fn inferrable(&self) -> bool {
+        false
+    }
+
+    fn printable(&self) -> bool {
+        false
+    }
+}
+
+impl CjkCodec {
+    fn encoding(&self) -> &'static Encoding {
+        match self.encoding {
+            CjkEncoding::ShiftJis => SHIFT_JIS,
+            CjkEncoding::EucJp => EUC_JP,
+            CjkEncoding::Iso2022Jp => ISO_2022_JP,
+            CjkEncoding::Gbk => GBK,
+            CjkEncoding::Gb18030 => GB18030,
+            CjkEncoding::Big5 => BIG5,
+            CjkEncoding::EucKr => EUC_KR,
+        }
+    }
+}
+
+#[test]
+fn encode() {
+    let tests = vec![
+        (
+            CjkEncoding::ShiftJis,
+            "日本語",
+            vec![0x93, 0xfa, 0x96, 0x7b, 0x8c, 0xea],
+        ),
+        (
+            CjkEncoding::EucJp,
+            "日本語",
+            vec![0xc6, 0xfc, 0xcb, 0xdc, 0xb8, 0xec],
+        ),
+        (
+            CjkEncoding::Iso2022Jp,
+            "a日本",
+            vec![
+                0x61, 0x1b, 0x24, 0x42, 0x46, 0x7c, 0x4b, 0x5c, 0x1b, 0x28, 0x42,
+            ],
+        ),
+        (CjkEncoding::Gbk, "中文", vec![0xd6, 0xd0, 0xce, 0xc4]),
+        (
+            CjkEncoding::Gb18030,
+            "中€😀",
+            vec![0xd6, 0xd0, 0xa2, 0xe3, 0x94, 0x39, 0xfc, 0x36],
+        ),
+        (CjkEncoding::Big5, "中文", vec![0xa4, 0xa4, 0xa4, 0xe5]),
+        (
+            CjkEncoding::EucKr,
+            "한국어",
+            vec![0xc7, 0xd1, 0xb1, 0xb9, 0xbe, 0xee],
+        ),
+    ];
+
+    for (encoding, text, encoded) in tests {
+        let codec = CjkCodec {
+            encoding,
+            replace: false,
+        };
+        assert_eq!(encoded, codec.encode(text.as_bytes().to_vec()).unwrap());
+        assert_eq!(text.as_bytes().to_vec(), codec.decode(encoded).unwrap());
+    }
+
+    let strict = CjkCodec {
+        encoding: CjkEncoding::ShiftJis,
+        replace: false,
+    };
+    assert_eq!(
+        "Invalid shift_jis sequence at byte 3",
+        strict
+            .decode(vec![0x61, 0x93, 0xfa, 0xff, 0x62])
+            .unwrap_err()
+            .to_string()
+    );
+    assert_eq!(
+        "euc-kr has no '😀' at byte 1",
+        CjkCodec {
+            encoding: CjkEncoding::EucKr,
+            replace: false,
+        }
+        .encode("a😀".as_bytes().to_vec())
+        .unwrap_err()
+        .to_string()
+    );
+    let lossy = CjkCodec {
+        encoding: CjkEncoding::ShiftJis,
+        replace: true,
+    };
+    assert_eq!(
+        "a日\u{fffd}b".as_bytes().to_vec(),
+        lossy.decode(vec![0x61, 0x93, 0xfa, 0xff, 0x62]).unwrap()
+    );
+}
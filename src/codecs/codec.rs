@@ -1,11 +1,64 @@
 use super::error::Error;
+use super::score;
+use super::stream;
+use super::stream::Direction;
 use crate::options::Format;
+use std::io::{Read, Write};
 
 pub trait Codec {
-    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error>;
-    fn encode(&self, data: Vec<u8>) -> Result<String, Error>;
+    fn decode(&self, _s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        Err(Error::new(format!("{} can't be decoded", self.format())))
+    }
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error>;
     fn format(&self) -> Format;
+
+    // One-way codecs, like hashes, only encode
+    fn decodable(&self) -> bool {
+        true
+    }
+
+    // Whether inference may pick this codec. Codecs that can't decode never are.
     fn inferrable(&self) -> bool {
+        self.decodable()
+    }
+
+    // Whether encoded output is always text, as opposed to bytes that
+    // have to be escaped before they can be shown
+    fn printable(&self) -> bool {
         true
     }
+
+    // How confident we are, from 0 to 1, that s was really in this format
+    // given that it decoded to decoded. Used to rank inferred formats.
+    fn confidence(&self, _s: &[u8], decoded: &[u8]) -> f64 {
+        score::weigh(0.5, decoded)
+    }
+
+    // How the format splits into groups that convert on their own, if it
+    // does. Without framing, streaming has to read all of the input first.
+    fn framing(&self) -> Option<Framing<'_>> {
+        None
+    }
+
+    fn decode_stream(&self, input: &mut dyn Read, output: &mut dyn Write) -> Result<(), Error> {
+        stream::copy(self, Direction::Decode, input, output)
+    }
+
+    fn encode_stream(&self, input: &mut dyn Read, output: &mut dyn Write) -> Result<(), Error> {
+        stream::copy(self, Direction::Encode, input, output)
+    }
+}
+
+pub struct Framing<'a> {
+    // Symbols of encoded text that decode on their own, not counting
+    // ignored ones
+    pub symbols: usize,
+    // Bytes of data that encode on their own
+    pub bytes: usize,
+    // Symbols that decoding skips over, like line breaks
+    pub ignored: fn(&u8) -> bool,
+    // What pads the last group, which can't come any earlier
+    pub padding: Option<u8>,
+    // What goes between separately encoded groups
+    pub separator: &'a str,
 }
@@ -0,0 +1,201 @@
+use super::base64::{Base64Codec, Base64Variant};
+use super::codec::Codec;
+use super::error::Error;
+use super::hex::HexCodec;
+use crate::Format;
+use sha2::Digest;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+    // BLAKE2b-512
+    Blake2b,
+    // BLAKE2s-256
+    Blake2s,
+    Blake3,
+}
+
+// How a digest is written out, since the digest itself is binary
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum DigestText {
+    Hex,
+    Base64,
+}
+
+pub struct DigestCodec {
+    pub algorithm: Algorithm,
+    pub text: DigestText,
+}
+
+impl Codec for DigestCodec {
+    fn format(&self) -> Format {
+        match self.algorithm {
+            Algorithm::Md5 => Format::Md5,
+            Algorithm::Sha1 => Format::Sha1,
+            Algorithm::Sha224 => Format::Sha224,
+            Algorithm::Sha256 => Format::Sha256,
+            Algorithm::Sha384 => Format::Sha384,
+            Algorithm::Sha512 => Format::Sha512,
+            Algorithm::Sha3_224 => Format::Sha3_224,
+            Algorithm::Sha3_256 => Format::Sha3_256,
+            Algorithm::Sha3_384 => Format::Sha3_384,
+            Algorithm::Sha3_512 => Format::Sha3_512,
+            Algorithm::Blake2b => Format::Blake2b,
+            Algorithm::Blake2s => Format::Blake2s,
+            Algorithm::Blake3 => Format::Blake3,
+        }
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        self.text.write(self.digest(&data))
+    }
+
+    fn decodable(&self) -> bool {
+        false
+    }
+}
+
+impl DigestCodec {
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self.algorithm {
+            Algorithm::Md5 => md5::Md5::digest(data).to_vec(),
+            Algorithm::Sha1 => sha1::Sha1::digest(data).to_vec(),
+            Algorithm::Sha224 => sha2::Sha224::digest(data).to_vec(),
+            Algorithm::Sha256 => sha2::Sha256::digest(data).to_vec(),
+            Algorithm::Sha384 => sha2::Sha384::digest(data).to_vec(),
+            Algorithm::Sha512 => sha2::Sha512::digest(data).to_vec(),
+            Algorithm::Sha3_224 => sha3::Sha3_224::digest(data).to_vec(),
+            Algorithm::Sha3_256 => sha3::Sha3_256::digest(data).to_vec(),
+            Algorithm::Sha3_384 => sha3::Sha3_384::digest(data).to_vec(),
+            Algorithm::Sha3_512 => sha3::Sha3_512::digest(data).to_vec(),
+            Algorithm::Blake2b => blake2::Blake2b512::digest(data).to_vec(),
+            Algorithm::Blake2s => blake2::Blake2s256::digest(data).to_vec(),
+            Algorithm::Blake3 => blake3::hash(data).as_bytes().to_vec(),
+        }
+    }
+}
+
+impl DigestText {
+    pub fn format(self) -> Format {
+        match self {
+            Self::Hex => Format::Hex,
+            Self::Base64 => Format::Base64,
+        }
+    }
+
+    pub fn write(self, digest: Vec<u8>) -> Result<Vec<u8>, Error> {
+        match self {
+            Self::Hex => HexCodec::default().encode(digest),
+            Self::Base64 => Base64Codec {
+                variant: Base64Variant::Standard,
+            }
+            .encode(digest),
+        }
+    }
+}
+
+impl Algorithm {
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::Md5,
+            Self::Sha1,
+            Self::Sha224,
+            Self::Sha256,
+            Self::Sha384,
+            Self::Sha512,
+            Self::Sha3_224,
+            Self::Sha3_256,
+            Self::Sha3_384,
+            Self::Sha3_512,
+            Self::Blake2b,
+            Self::Blake2s,
+            Self::Blake3,
+        ]
+    }
+}
+
+#[test]
+fn encode() {
+    use std::collections::HashMap;
+
+    let tests: HashMap<Algorithm, &str> = [
+        (Algorithm::Md5, "900150983cd24fb0d6963f7d28e17f72"),
+        (Algorithm::Sha1, "a9993e364706816aba3e25717850c26c9cd0d89d"),
+        (
+            Algorithm::Sha224,
+            "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+        ),
+        (
+            Algorithm::Sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            Algorithm::Sha384,
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+        ),
+        (
+            Algorithm::Sha512,
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+        ),
+        (
+            Algorithm::Sha3_224,
+            "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf",
+        ),
+        (
+            Algorithm::Sha3_256,
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+        ),
+        (
+            Algorithm::Sha3_384,
+            "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25",
+        ),
+        (
+            Algorithm::Sha3_512,
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+        ),
+        (
+            Algorithm::Blake2b,
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        ),
+        (
+            Algorithm::Blake2s,
+            "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+        ),
+        (
+            Algorithm::Blake3,
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+        ),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    for (algorithm, expected) in tests {
+        let codec = DigestCodec {
+            algorithm,
+            text: DigestText::Hex,
+        };
+        assert_eq!(
+            expected.as_bytes().to_vec(),
+            codec.encode(b"abc".to_vec()).unwrap()
+        );
+    }
+
+    let codec = DigestCodec {
+        algorithm: Algorithm::Sha256,
+        text: DigestText::Base64,
+    };
+    assert_eq!(
+        b"ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=".to_vec(),
+        codec.encode(b"abc".to_vec()).unwrap()
+    );
+}
@@ -1,10 +1,33 @@
+use std::fmt;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Error {
     pub err: String,
+    // Byte offset into the input where the error occurred, if known
+    pub offset: Option<usize>,
 }
 
 impl Error {
     pub fn new(e: String) -> Self {
-        Self { err: e }
+        Self {
+            err: e,
+            offset: None,
+        }
+    }
+
+    pub fn at(e: String, offset: usize) -> Self {
+        Self {
+            err: e,
+            offset: Some(offset),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.offset {
+            Some(offset) => write!(f, "{} at byte {}", self.err, offset),
+            None => write!(f, "{}", self.err),
+        }
     }
 }
@@ -0,0 +1,783 @@
+use super::codec::Codec;
+use super::error::Error;
+use crate::Format;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum EscapeSyntax {
+    // "...", with octal for anything unprintable so digits after an escape
+    // can't run into it
+    C,
+    // "...", with \uXXXX and surrogate pairs
+    Json,
+    // "..." with \u{...}, or b"..." for bytes that aren't utf8
+    Rust,
+    // "..." with \x, \u and \U, or b"..." for bytes that aren't utf8
+    Python,
+    // Like JSON, plus \x, \v, \u{...} and single quotes and backticks
+    JavaScript,
+    // A single bash word: bare if it's safe, '...' if it's text, and $'...'
+    // otherwise. Bash itself cuts arguments off at a NUL.
+    Shell,
+}
+
+// Bytes written as a string literal in some language's syntax, and read back
+// from one. Apart from shell words, output is plain ASCII so it can be pasted
+// into source in any encoding. C, Rust, Python and shell literals can hold
+// any bytes; JSON and JavaScript strings only hold text.
+pub struct EscapeCodec {
+    pub syntax: EscapeSyntax,
+}
+
+impl Codec for EscapeCodec {
+    fn format(&self) -> Format {
+        match self.syntax {
+            EscapeSyntax::C => Format::CString,
+            EscapeSyntax::Json => Format::JsonString,
+            EscapeSyntax::Rust => Format::RustString,
+            EscapeSyntax::Python => Format::PythonString,
+            EscapeSyntax::JavaScript => Format::JsString,
+            EscapeSyntax::Shell => Format::Shell,
+        }
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        if self.syntax == EscapeSyntax::Shell {
+            return self.unquote_shell(&s);
+        }
+        let literal = self.literal(&s)?;
+        if literal.raw {
+            let body = &s[literal.start..literal.end];
+            if !literal.bytes {
+                std::str::from_utf8(body).map_err(|e| {
+                    Error::at("invalid utf8".to_string(), literal.start + e.valid_up_to())
+                })?;
+            }
+            return Ok(body.to_vec());
+        }
+        self.unescape(&s, &literal)
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let out = match (self.syntax, String::from_utf8(data)) {
+            (EscapeSyntax::Shell, text) => {
+                quote_shell(&text.map_or_else(|e| e.into_bytes(), String::into_bytes))
+            }
+            (EscapeSyntax::C, text) => format!(
+                "\"{}\"",
+                text.map_or_else(|e| e.into_bytes(), String::into_bytes)
+                    .iter()
+                    .map(|&b| self.escape_byte(b))
+                    .collect::<String>()
+            ),
+            (_, Ok(text)) => format!(
+                "\"{}\"",
+                text.chars()
+                    .map(|c| self.escape_char(c))
+                    .collect::<String>()
+            ),
+            (EscapeSyntax::Rust, Err(e)) | (EscapeSyntax::Python, Err(e)) => format!(
+                "b\"{}\"",
+                e.as_bytes()
+                    .iter()
+                    .map(|&b| self.escape_byte(b))
+                    .collect::<String>()
+            ),
+            (_, Err(e)) => {
+                return Err(Error::at(
+                    format!("{} can only hold utf8", self.format()),
+                    e.utf8_error().valid_up_to(),
+                ))
+            }
+        };
+        Ok(out.into_bytes())
+    }
+
+    fn inferrable(&self) -> bool {
+        // Text without quotes or backslashes would decode to itself
+        false
+    }
+}
+
+// Where the inside of a literal is, and how to read it
+struct Literal {
+    start: usize,
+    end: usize,
+    // The quote that ends the literal, which has to be escaped inside it.
+    // None for input that wasn't quoted.
+    quote: Option<u8>,
+    // A bytes literal, b"..." in Rust and Python
+    bytes: bool,
+    // A raw literal, where backslashes are just backslashes
+    raw: bool,
+}
+
+impl EscapeCodec {
+    // Finds the quotes and any prefix. Input that doesn't start with them is
+    // taken as the inside of a literal, but an opening quote has to close.
+    fn literal(&self, s: &[u8]) -> Result<Literal, Error> {
+        let start = s.len() - s.trim_ascii_start().len();
+        let end = s.trim_ascii_end().len().max(start);
+        let trimmed = &s[start..end];
+        let prefixes: &[&[u8]] = match self.syntax {
+            EscapeSyntax::Rust => &[b"", b"b", b"r", b"br"],
+            EscapeSyntax::Python => &[
+                b"", b"b", b"B", b"r", b"R", b"u", b"U", b"br", b"rb", b"Br", b"bR", b"BR", b"RB",
+                b"Rb", b"rB",
+            ],
+            _ => &[b""],
+        };
+        let quotes: &[u8] = match self.syntax {
+            EscapeSyntax::Python => b"\"'",
+            EscapeSyntax::JavaScript => b"\"'`",
+            _ => b"\"",
+        };
+        // Longest prefix first, so b"" isn't taken as b followed by ""
+        for prefix in prefixes.iter().rev() {
+            let rest = match trimmed.strip_prefix(*prefix) {
+                Some(rest) => rest,
+                None => continue,
+            };
+            let raw = prefix.contains(&b'r') || prefix.contains(&b'R');
+            // Rust's raw strings can be fenced with #s
+            let hashes = match self.syntax {
+                EscapeSyntax::Rust if raw => rest.iter().take_while(|&&c| c == b'#').count(),
+                _ => 0,
+            };
+            let rest = &rest[hashes..];
+            let quote = match rest.first() {
+                Some(quote) if quotes.contains(quote) => *quote,
+                _ => continue,
+            };
+            if self.syntax == EscapeSyntax::Python && rest.starts_with(&[quote; 3]) {
+                return Err(Error::at(
+                    format!("Triple quotes aren't supported in {}", self.format()),
+                    start + prefix.len(),
+                ));
+            }
+            let closing = std::iter::once(quote)
+                .chain(std::iter::repeat_n(b'#', hashes))
+                .collect::<Vec<u8>>();
+            if rest.len() < closing.len() + 1 || !rest.ends_with(&closing) {
+                return Err(Error::at(
+                    format!("Unterminated quote in {}", self.format()),
+                    start + prefix.len() + hashes,
+                ));
+            }
+            let body = start + prefix.len() + hashes + 1;
+            return Ok(Literal {
+                start: body,
+                end: end - closing.len(),
+                quote: Some(quote),
+                bytes: self.syntax == EscapeSyntax::C
+                    || prefix.contains(&b'b')
+                    || prefix.contains(&b'B'),
+                raw,
+            });
+        }
+        Ok(Literal {
+            start: 0,
+            end: s.len(),
+            quote: None,
+            bytes: self.syntax == EscapeSyntax::C,
+            raw: false,
+        })
+    }
+
+    fn unescape(&self, s: &[u8], literal: &Literal) -> Result<Vec<u8>, Error> {
+        let mut out = vec![];
+        let mut i = literal.start;
+        while i < literal.end {
+            let b = s[i];
+            if Some(b) == literal.quote {
+                return Err(Error::at(
+                    format!("Unescaped {} in {}", b as char, self.format()),
+                    i,
+                ));
+            }
+            if b != b'\\' {
+                out.push(b);
+                i += 1;
+                continue;
+            }
+            let at = i;
+            let e = *s
+                .get(i + 1)
+                .filter(|_| i + 1 < literal.end)
+                .ok_or_else(|| Error::at(format!("Unfinished escape in {}", self.format()), at))?;
+            i += 2;
+            let invalid = || {
+                Error::at(
+                    format!("Invalid escape \\{} in {}", e as char, self.format()),
+                    at,
+                )
+            };
+            let digits = |i: &mut usize, radix: u32, min: usize, max: usize| {
+                read_digits(&s[..literal.end], i, radix, min, max).ok_or_else(invalid)
+            };
+            let syntax = self.syntax;
+            let text = !literal.bytes;
+            match (syntax, e) {
+                (_, b'\\') | (_, b'"') => out.push(e),
+                (EscapeSyntax::C, b'?') => out.push(e),
+                (EscapeSyntax::Json, b'/') => out.push(e),
+                (EscapeSyntax::Json, b'\'') => return Err(invalid()),
+                (_, b'\'') => out.push(e),
+                (_, b'n') => out.push(b'\n'),
+                (_, b'r') => out.push(b'\r'),
+                (_, b't') => out.push(b'\t'),
+                (EscapeSyntax::Rust, b'0') => out.push(0),
+                (EscapeSyntax::JavaScript, b'0') if !s.get(i).is_some_and(u8::is_ascii_digit) => {
+                    out.push(0)
+                }
+                (EscapeSyntax::C, b'a') | (EscapeSyntax::Python, b'a') => out.push(0x07),
+                (EscapeSyntax::Rust, b'b') => return Err(invalid()),
+                (_, b'b') => out.push(0x08),
+                (EscapeSyntax::Rust, b'f') => return Err(invalid()),
+                (_, b'f') => out.push(0x0c),
+                (EscapeSyntax::C, b'v')
+                | (EscapeSyntax::Python, b'v')
+                | (EscapeSyntax::JavaScript, b'v') => out.push(0x0b),
+                // A backslash before a line break continues the line, and
+                // in Rust skips the indentation after it too
+                (EscapeSyntax::Rust, b'\n') => {
+                    while i < literal.end && s[i].is_ascii_whitespace() {
+                        i += 1;
+                    }
+                }
+                (EscapeSyntax::Json, b'\n') => return Err(invalid()),
+                (_, b'\n') => (),
+                (EscapeSyntax::C, b'0'..=b'7') | (EscapeSyntax::Python, b'0'..=b'7') => {
+                    i -= 1;
+                    let value = digits(&mut i, 8, 1, 3)?;
+                    self.push(&mut out, value, text && syntax == EscapeSyntax::Python, at)?;
+                }
+                // C's \x takes as many digits as follow it
+                (EscapeSyntax::C, b'x') => {
+                    let value = digits(&mut i, 16, 1, 8)?;
+                    self.push(&mut out, value, false, at)?;
+                }
+                (EscapeSyntax::Rust, b'x') => {
+                    let value = digits(&mut i, 16, 2, 2)?;
+                    if text && value > 0x7f {
+                        return Err(Error::at(
+                            format!("\\x{:02x} is past ASCII in {}", value, self.format()),
+                            at,
+                        ));
+                    }
+                    out.push(value as u8);
+                }
+                (EscapeSyntax::Python, b'x') | (EscapeSyntax::JavaScript, b'x') => {
+                    let value = digits(&mut i, 16, 2, 2)?;
+                    self.push(&mut out, value, text, at)?;
+                }
+                (EscapeSyntax::Rust, b'u') | (EscapeSyntax::JavaScript, b'u')
+                    if s.get(i) == Some(&b'{') && text =>
+                {
+                    i += 1;
+                    let value = digits(&mut i, 16, 1, 6)?;
+                    if s.get(i) != Some(&b'}') {
+                        return Err(invalid());
+                    }
+                    i += 1;
+                    self.push(&mut out, value, true, at)?;
+                }
+                (EscapeSyntax::Json, b'u') | (EscapeSyntax::JavaScript, b'u') => {
+                    let unit = digits(&mut i, 16, 4, 4)?;
+                    let value = match unit {
+                        0xd800..=0xdbff if s[i..literal.end].starts_with(b"\\u") => {
+                            i += 2;
+                            match digits(&mut i, 16, 4, 4)? {
+                                low @ 0xdc00..=0xdfff => {
+                                    0x10000 + ((unit - 0xd800) << 10) + (low - 0xdc00)
+                                }
+                                _ => return Err(unpaired(unit, at)),
+                            }
+                        }
+                        0xd800..=0xdfff => return Err(unpaired(unit, at)),
+                        _ => unit,
+                    };
+                    self.push(&mut out, value, true, at)?;
+                }
+                (EscapeSyntax::C, b'u') | (EscapeSyntax::Python, b'u')
+                    if text || syntax == EscapeSyntax::C =>
+                {
+                    let value = digits(&mut i, 16, 4, 4)?;
+                    self.push(&mut out, value, true, at)?;
+                }
+                (EscapeSyntax::C, b'U') | (EscapeSyntax::Python, b'U')
+                    if text || syntax == EscapeSyntax::C =>
+                {
+                    let value = digits(&mut i, 16, 8, 8)?;
+                    self.push(&mut out, value, true, at)?;
+                }
+                // Python keeps the backslash of an escape it doesn't know
+                (EscapeSyntax::Python, _) => out.extend([b'\\', e]),
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(out)
+    }
+
+    // Adds an escaped value, as a code point in utf8 or as a byte
+    fn push(&self, out: &mut Vec<u8>, value: u32, char: bool, at: usize) -> Result<(), Error> {
+        if !char {
+            if value > 0xff {
+                return Err(Error::at(
+                    format!(
+                        "Escape {:x} is more than a byte in {}",
+                        value,
+                        self.format()
+                    ),
+                    at,
+                ));
+            }
+            out.push(value as u8);
+            return Ok(());
+        }
+        let c = char::from_u32(value)
+            .ok_or_else(|| Error::at(format!("Invalid code point {:x}", value), at))?;
+        let mut buf = [0; 4];
+        out.extend(c.encode_utf8(&mut buf).as_bytes());
+        Ok(())
+    }
+
+    // A byte in C or in a Rust or Python bytes literal
+    fn escape_byte(&self, b: u8) -> String {
+        match (self.syntax, b) {
+            (_, b'"') => "\\\"".to_string(),
+            (_, b'\\') => "\\\\".to_string(),
+            (_, b'\n') => "\\n".to_string(),
+            (_, b'\r') => "\\r".to_string(),
+            (_, b'\t') => "\\t".to_string(),
+            (_, 0x20..=0x7e) => (b as char).to_string(),
+            (EscapeSyntax::C, 0x07) => "\\a".to_string(),
+            (EscapeSyntax::C, 0x08) => "\\b".to_string(),
+            (EscapeSyntax::C, 0x0b) => "\\v".to_string(),
+            (EscapeSyntax::C, 0x0c) => "\\f".to_string(),
+            (EscapeSyntax::C, _) => format!("\\{:03o}", b),
+            (EscapeSyntax::Rust, 0) => "\\0".to_string(),
+            _ => format!("\\x{:02x}", b),
+        }
+    }
+
+    // A character in a JSON, JavaScript, Rust or Python string
+    fn escape_char(&self, c: char) -> String {
+        let syntax = self.syntax;
+        let json = matches!(syntax, EscapeSyntax::Json | EscapeSyntax::JavaScript);
+        match c {
+            '"' => "\\\"".to_string(),
+            '\\' => "\\\\".to_string(),
+            '\n' => "\\n".to_string(),
+            '\r' => "\\r".to_string(),
+            '\t' => "\\t".to_string(),
+            ' '..='~' => c.to_string(),
+            '\u{8}' if json => "\\b".to_string(),
+            '\u{c}' if json => "\\f".to_string(),
+            '\u{b}' if syntax == EscapeSyntax::JavaScript => "\\v".to_string(),
+            '\0' if syntax == EscapeSyntax::Rust => "\\0".to_string(),
+            _ => match (syntax, c as u32) {
+                (EscapeSyntax::Rust, n @ 0..=0x7f) => format!("\\x{:02x}", n),
+                (EscapeSyntax::Rust, n) => format!("\\u{{{:x}}}", n),
+                (EscapeSyntax::Python, n @ 0..=0xff) | (EscapeSyntax::JavaScript, n @ 0..=0xff) => {
+                    format!("\\x{:02x}", n)
+                }
+                (EscapeSyntax::Python, n @ 0..=0xffff) => format!("\\u{:04x}", n),
+                (EscapeSyntax::Python, n) => format!("\\U{:08x}", n),
+                // Past the BMP as a surrogate pair
+                _ => c
+                    .encode_utf16(&mut [0; 2])
+                    .iter()
+                    .map(|unit| format!("\\u{:04x}", unit))
+                    .collect(),
+            },
+        }
+    }
+
+    // Reads a single bash word, made of bare, '...', "..." and $'...' parts
+    fn unquote_shell(&self, s: &[u8]) -> Result<Vec<u8>, Error> {
+        let start = s.len() - s.trim_ascii_start().len();
+        let end = s.trim_ascii_end().len().max(start);
+        let unterminated = |quote: &str, at: usize| {
+            Error::at(format!("Unterminated {} in {}", quote, self.format()), at)
+        };
+        let expansion = |at: usize| {
+            Error::at(
+                format!("Can't expand {} in {}", s[at] as char, self.format()),
+                at,
+            )
+        };
+        let mut out = vec![];
+        let mut i = start;
+        while i < end {
+            match s[i] {
+                b'\'' => {
+                    let close = s[i + 1..end]
+                        .iter()
+                        .position(|&c| c == b'\'')
+                        .ok_or_else(|| unterminated("'", i))?;
+                    out.extend(&s[i + 1..i + 1 + close]);
+                    i += close + 2;
+                }
+                b'$' if s.get(i + 1) == Some(&b'\'') && i + 1 < end => {
+                    let open = i;
+                    i += 2;
+                    loop {
+                        match s[..end].get(i) {
+                            None => return Err(unterminated("$'", open)),
+                            Some(b'\'') => break,
+                            Some(b'\\') => i = self.ansi_c(s, end, i + 1, &mut out, open)?,
+                            Some(&c) => {
+                                out.push(c);
+                                i += 1;
+                            }
+                        }
+                    }
+                    i += 1;
+                }
+                b'"' => {
+                    let open = i;
+                    i += 1;
+                    loop {
+                        match s[..end].get(i) {
+                            None => return Err(unterminated("\"", open)),
+                            Some(b'"') => break,
+                            Some(b'$') | Some(b'`') => return Err(expansion(i)),
+                            Some(b'\\') => match s[..end].get(i + 1) {
+                                Some(b'\n') => i += 2,
+                                Some(&c @ (b'$' | b'`' | b'"' | b'\\')) => {
+                                    out.push(c);
+                                    i += 2;
+                                }
+                                _ => {
+                                    out.push(b'\\');
+                                    i += 1;
+                                }
+                            },
+                            Some(&c) => {
+                                out.push(c);
+                                i += 1;
+                            }
+                        }
+                    }
+                    i += 1;
+                }
+                b'\\' => {
+                    match s[..end].get(i + 1) {
+                        Some(b'\n') => (),
+                        Some(&c) => out.push(c),
+                        None => {
+                            return Err(Error::at(
+                                format!("Unfinished escape in {}", self.format()),
+                                i,
+                            ))
+                        }
+                    }
+                    i += 2;
+                }
+                b'$' | b'`' => return Err(expansion(i)),
+                c if c.is_ascii_whitespace() || b";&|<>()*?[".contains(&c) => {
+                    return Err(Error::at(
+                        format!("Unquoted {:?} in {}", c as char, self.format()),
+                        i,
+                    ))
+                }
+                c => {
+                    out.push(c);
+                    i += 1;
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    // Reads the escape after a backslash in $'...', returning where it ends
+    fn ansi_c(
+        &self,
+        s: &[u8],
+        end: usize,
+        i: usize,
+        out: &mut Vec<u8>,
+        open: usize,
+    ) -> Result<usize, Error> {
+        let e = *s[..end]
+            .get(i)
+            .ok_or_else(|| Error::at(format!("Unterminated $' in {}", self.format()), open))?;
+        let digits = |from: usize, radix: u32, max: usize| {
+            let mut next = from;
+            (read_digits(&s[..end], &mut next, radix, 1, max), next)
+        };
+        let simple = match e {
+            b'a' => Some(0x07),
+            b'b' => Some(0x08),
+            b'e' | b'E' => Some(0x1b),
+            b'f' => Some(0x0c),
+            b'n' => Some(b'\n'),
+            b'r' => Some(b'\r'),
+            b't' => Some(b'\t'),
+            b'v' => Some(0x0b),
+            b'\\' | b'\'' | b'"' | b'?' => Some(e),
+            _ => None,
+        };
+        if let Some(b) = simple {
+            out.push(b);
+            return Ok(i + 1);
+        }
+        let (value, next, char) = match e {
+            b'0'..=b'7' => {
+                let (value, next) = digits(i, 8, 3);
+                (value, next, false)
+            }
+            b'x' => {
+                let (value, next) = digits(i + 1, 16, 2);
+                (value, next, false)
+            }
+            b'u' => {
+                let (value, next) = digits(i + 1, 16, 4);
+                (value, next, true)
+            }
+            b'U' => {
+                let (value, next) = digits(i + 1, 16, 8);
+                (value, next, true)
+            }
+            b'c' if i + 1 < end => (Some(s[i + 1] as u32 & 0x1f), i + 2, false),
+            // Bash keeps the backslash of an escape it doesn't know
+            _ => {
+                out.extend([b'\\', e]);
+                return Ok(i + 1);
+            }
+        };
+        match value {
+            // \x with no digits after it is left as it is
+            None => {
+                out.extend(&s[i - 1..next]);
+                Ok(next)
+            }
+            Some(value) => {
+                self.push(out, value & if char { u32::MAX } else { 0xff }, char, i - 1)?;
+                Ok(next)
+            }
+        }
+    }
+}
+
+// Quotes as little as bash needs
+fn quote_shell(data: &[u8]) -> String {
+    let safe = |c: &u8| c.is_ascii_alphanumeric() || b"@%+=:,./_-".contains(c);
+    if !data.is_empty() && data.iter().all(safe) {
+        // Safe since these are all ASCII
+        return String::from_utf8(data.to_vec()).unwrap();
+    }
+    match std::str::from_utf8(data) {
+        Ok(text) if !text.chars().any(char::is_control) => {
+            format!("'{}'", text.replace('\'', "'\\''"))
+        }
+        _ => format!(
+            "$'{}'",
+            data.iter()
+                .map(|&b| match b {
+                    b'\'' => "\\'".to_string(),
+                    b'\\' => "\\\\".to_string(),
+                    b'\n' => "\\n".to_string(),
+                    b'\r' => "\\r".to_string(),
+                    b'\t' => "\\t".to_string(),
+                    0x20..=0x7e => (b as char).to_string(),
+                    _ => format!("\\x{:02x}", b),
+                })
+                .collect::<String>()
+        ),
+    }
+}
+
+// Reads min to max digits in the radix from s at i, moving i past them
+fn read_digits(s: &[u8], i: &mut usize, radix: u32, min: usize, max: usize) -> Option<u32> {
+    let n = s[*i..]
+        .iter()
+        .take(max)
+        .take_while(|c| (**c as char).is_digit(radix))
+        .count();
+    if n < min {
+        return None;
+    }
+    // Safe since these are all digits, and few enough to fit
+    let value = u32::from_str_radix(std::str::from_utf8(&s[*i..*i + n]).unwrap(), radix).unwrap();
+    *i += n;
+    Some(value)
+}
+
+fn unpaired(unit: u32, offset: usize) -> Error {
+    Error::at(format!("Unpaired surrogate {:04x}", unit), offset)
+}
+
+#[test]
+fn encode() {
+    let tests = vec![
+        (
+            EscapeSyntax::C,
+            "tab\t\"q\"\u{7}é",
+            "\"tab\\t\\\"q\\\"\\a\\303\\251\"",
+        ),
+        (
+            EscapeSyntax::Json,
+            "a\"\n\u{1}é😀",
+            "\"a\\\"\\n\\u0001\\u00e9\\ud83d\\ude00\"",
+        ),
+        (
+            EscapeSyntax::JavaScript,
+            "a\u{b}\u{1}é😀",
+            "\"a\\v\\x01\\xe9\\ud83d\\ude00\"",
+        ),
+        (
+            EscapeSyntax::Rust,
+            "a\0\u{7f}é😀",
+            "\"a\\0\\x7f\\u{e9}\\u{1f600}\"",
+        ),
+        (
+            EscapeSyntax::Python,
+            "a\u{1}é€😀",
+            "\"a\\x01\\xe9\\u20ac\\U0001f600\"",
+        ),
+        (EscapeSyntax::Shell, "file-1.txt", "file-1.txt"),
+        (EscapeSyntax::Shell, "it's here", "'it'\\''s here'"),
+        (EscapeSyntax::Shell, "", "''"),
+        (EscapeSyntax::Shell, "a\tb", "$'a\\tb'"),
+    ];
+
+    for (syntax, text, expected) in tests {
+        let codec = EscapeCodec { syntax };
+        assert_eq!(
+            expected.as_bytes().to_vec(),
+            codec.encode(text.as_bytes().to_vec()).unwrap()
+        );
+        assert_eq!(
+            text.as_bytes().to_vec(),
+            codec.decode(expected.as_bytes().to_vec()).unwrap()
+        );
+    }
+
+    // Raw bytes round-trip wherever the syntax can hold them
+    let bytes = (0..=255).collect::<Vec<u8>>();
+    for syntax in [
+        EscapeSyntax::C,
+        EscapeSyntax::Rust,
+        EscapeSyntax::Python,
+        EscapeSyntax::Shell,
+    ] {
+        let codec = EscapeCodec { syntax };
+        let encoded = codec.encode(bytes.clone()).unwrap();
+        assert!(encoded.is_ascii());
+        assert_eq!(bytes, codec.decode(encoded).unwrap());
+    }
+    assert_eq!(
+        "json string can only hold utf8 at byte 1",
+        EscapeCodec {
+            syntax: EscapeSyntax::Json
+        }
+        .encode(vec![0x61, 0xff])
+        .unwrap_err()
+        .to_string()
+    );
+}
+
+#[test]
+fn decode() {
+    let tests = vec![
+        (EscapeSyntax::C, "\"\\x41\\101\\0\\u00e9\"", "AA\0é"),
+        (EscapeSyntax::C, "no quotes\\n", "no quotes\n"),
+        (EscapeSyntax::Json, "  \"\\ud83d\\ude00\\/\"\n", "😀/"),
+        (EscapeSyntax::JavaScript, "'it\\'s \\u{1F600}'", "it's 😀"),
+        (
+            EscapeSyntax::Rust,
+            "r#\"raw \\n \"quoted\"\"#",
+            "raw \\n \"quoted\"",
+        ),
+        (EscapeSyntax::Rust, "\"one \\\n      line\"", "one line"),
+        (EscapeSyntax::Python, "'\\d stays'", "\\d stays"),
+        (EscapeSyntax::Python, "u\"caf\\xe9\"", "café"),
+        (
+            EscapeSyntax::Shell,
+            "'a b'\"c\\\"d\"$'\\x41\\u00e9'\\ e",
+            "a bc\"dAé e",
+        ),
+    ];
+
+    for (syntax, input, expected) in tests {
+        let codec = EscapeCodec { syntax };
+        assert_eq!(
+            expected.as_bytes().to_vec(),
+            codec.decode(input.as_bytes().to_vec()).unwrap()
+        );
+    }
+
+    let bytes = vec![
+        (EscapeSyntax::Rust, "b\"\\xff\\x00\"", vec![0xff, 0x00]),
+        (EscapeSyntax::Python, "b'\\xff\\377'", vec![0xff, 0xff]),
+        (EscapeSyntax::Python, "'\\xff'", vec![0xc3, 0xbf]),
+    ];
+    for (syntax, input, expected) in bytes {
+        let codec = EscapeCodec { syntax };
+        assert_eq!(expected, codec.decode(input.as_bytes().to_vec()).unwrap());
+    }
+
+    let errors = vec![
+        (
+            EscapeSyntax::Json,
+            "\"\\ud83d\"",
+            "Unpaired surrogate d83d at byte 1",
+        ),
+        (
+            EscapeSyntax::Json,
+            "\"a\\q\"",
+            "Invalid escape \\q in json string at byte 2",
+        ),
+        (
+            EscapeSyntax::C,
+            "\"a\"b\"",
+            "Unescaped \" in c string at byte 2",
+        ),
+        (
+            EscapeSyntax::Rust,
+            "\"\\xff\"",
+            "\\xff is past ASCII in rust string at byte 1",
+        ),
+        (
+            EscapeSyntax::Json,
+            "\"abc",
+            "Unterminated quote in json string at byte 0",
+        ),
+        (
+            EscapeSyntax::Rust,
+            "r#\"abc\"",
+            "Unterminated quote in rust string at byte 2",
+        ),
+        (
+            EscapeSyntax::Python,
+            "\"\"\"doc\"\"\"",
+            "Triple quotes aren't supported in python string at byte 0",
+        ),
+        (
+            EscapeSyntax::Shell,
+            "a b",
+            "Unquoted ' ' in shell quoted at byte 1",
+        ),
+        (
+            EscapeSyntax::Shell,
+            "\"$HOME\"",
+            "Can't expand $ in shell quoted at byte 1",
+        ),
+        (
+            EscapeSyntax::Shell,
+            "'open",
+            "Unterminated ' in shell quoted at byte 0",
+        ),
+    ];
+    for (syntax, input, expected) in errors {
+        let codec = EscapeCodec { syntax };
+        assert_eq!(
+            expected,
+            codec
+                .decode(input.as_bytes().to_vec())
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
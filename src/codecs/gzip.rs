@@ -0,0 +1,51 @@
+use super::codec::Codec;
+use super::error::Error;
+use super::score;
+use crate::Format;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io;
+use std::io::{Read, Write};
+
+pub struct GzipCodec {}
+
+impl Codec for GzipCodec {
+    fn format(&self) -> Format {
+        Format::Gzip
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        if !s.starts_with(&[0x1f, 0x8b]) {
+            return Err(Error::new("Missing gzip header".to_string()));
+        }
+        let mut out = vec![];
+        MultiGzDecoder::new(&s[..])
+            .read_to_end(&mut out)
+            .map_err(|e| Error::new(format!("Invalid gzip data: {}", e)))?;
+        Ok(out)
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let mut gz = GzEncoder::new(vec![], Compression::default());
+        gz.write_all(&data)
+            .and_then(|_| gz.finish())
+            .map_err(|e| Error::new(format!("Couldn't compress: {}", e)))
+    }
+
+    fn printable(&self) -> bool {
+        false
+    }
+
+    // The decoder reads its own input, so it doesn't need framing to stream
+    fn decode_stream(&self, input: &mut dyn Read, output: &mut dyn Write) -> Result<(), Error> {
+        io::copy(&mut MultiGzDecoder::new(input), output)
+            .map(|_| ())
+            .map_err(|e| Error::new(format!("Invalid gzip data: {}", e)))
+    }
+
+    fn confidence(&self, _s: &[u8], decoded: &[u8]) -> f64 {
+        // The header and checksum make gzip unmistakable
+        score::weigh(1.0, decoded)
+    }
+}
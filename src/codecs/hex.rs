@@ -1,8 +1,82 @@
-use super::codec::Codec;
+use super::codec::{Codec, Framing};
 use super::error::Error;
+use super::score;
+use super::stream;
+use super::stream::Direction;
 use crate::Format;
+use std::io::{Cursor, Read, Write};
 
-pub struct HexCodec {}
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum HexStyle {
+    // deadbeef
+    Plain,
+    // 0xde, 0xad, 0xbe, 0xef
+    Prefixed,
+    // \xde\xad\xbe\xef
+    Escaped,
+    // Ready to paste into source code as an array or bytes literal
+    C,
+    Rust,
+    Python,
+    Go,
+    Java,
+}
+
+impl HexStyle {
+    pub fn all_variants() -> Vec<&'static str> {
+        vec!["plain", "0x", "\\x", "c", "rust", "python", "go", "java"]
+    }
+
+    // What goes between bytes unless told otherwise
+    pub fn separator(self) -> &'static str {
+        match self {
+            Self::Plain | Self::Escaped | Self::Python => "",
+            _ => ", ",
+        }
+    }
+
+    fn literal(self) -> bool {
+        !matches!(self, Self::Plain | Self::Prefixed | Self::Escaped)
+    }
+}
+
+impl std::str::FromStr for HexStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "plain" => Ok(Self::Plain),
+            "0x" => Ok(Self::Prefixed),
+            "\\x" => Ok(Self::Escaped),
+            "c" => Ok(Self::C),
+            "rust" => Ok(Self::Rust),
+            "python" => Ok(Self::Python),
+            "go" => Ok(Self::Go),
+            "java" => Ok(Self::Java),
+            _ => Err(Error::new(format!("Unknown hex style {}", s))),
+        }
+    }
+}
+
+// Encodes in any of the styles, and decodes all of them whatever the style:
+// separators, 0x and \x prefixes and the literals around the bytes are all
+// skipped over
+pub struct HexCodec {
+    pub style: HexStyle,
+    pub upper: bool,
+    // Goes between bytes, except in a Python bytes literal
+    pub separator: String,
+}
+
+impl Default for HexCodec {
+    fn default() -> Self {
+        Self {
+            style: HexStyle::Plain,
+            upper: false,
+            separator: HexStyle::Plain.separator().to_string(),
+        }
+    }
+}
 
 impl Codec for HexCodec {
     fn format(&self) -> Format {
@@ -10,6 +84,9 @@ impl Codec for HexCodec {
     }
 
     fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        if !Self::plain(&s) {
+            return Self::parse(&s);
+        }
         if s.len() % 2 == 1 {
             return Err(Error::new(
                 "Invalid number of characters for hex string".to_string(),
@@ -27,37 +104,297 @@ impl Codec for HexCodec {
             })
     }
 
-    fn encode(&self, data: Vec<u8>) -> Result<String, Error> {
-        Ok(data
-            .into_iter()
-            .map(|byte| {
-                format!(
-                    "{}{}",
-                    Self::nibble_to_char(byte >> 4),
-                    Self::nibble_to_char(byte & 0b00001111)
-                )
-            })
-            .collect::<Vec<String>>()
-            .join(""))
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let digits = |b: &u8| {
+            if self.upper {
+                format!("{:02X}", b)
+            } else {
+                format!("{:02x}", b)
+            }
+        };
+        let prefixed = |b: &u8| format!("0x{}", digits(b));
+        let escaped = |b: &u8| format!("\\x{}", digits(b));
+        let join = |f: &dyn Fn(&u8) -> String| {
+            data.iter()
+                .map(f)
+                .collect::<Vec<String>>()
+                .join(&self.separator)
+        };
+        let n = data.len();
+        let out = match self.style {
+            HexStyle::Plain => join(&digits),
+            HexStyle::Prefixed => join(&prefixed),
+            HexStyle::Escaped => join(&escaped),
+            HexStyle::C => format!("uint8_t data[{}] = {{{}}};", n, join(&prefixed)),
+            HexStyle::Rust => format!("let data: [u8; {}] = [{}];", n, join(&prefixed)),
+            HexStyle::Python => format!(
+                "data = b\"{}\"",
+                data.iter().map(escaped).collect::<String>()
+            ),
+            HexStyle::Go => format!("data := []byte{{{}}}", join(&prefixed)),
+            // Java's bytes are signed, so the high ones need a cast
+            HexStyle::Java => format!(
+                "byte[] data = {{{}}};",
+                join(&|b| match b {
+                    0x80..=0xff => format!("(byte) {}", prefixed(b)),
+                    _ => prefixed(b),
+                })
+            ),
+        };
+        Ok(out.into_bytes())
+    }
+
+    // Literals only make sense whole, but bytes in the other styles can be
+    // written one at a time
+    fn framing(&self) -> Option<Framing<'_>> {
+        if self.style.literal() {
+            return None;
+        }
+        Some(Framing {
+            symbols: 2,
+            bytes: 1,
+            ignored: u8::is_ascii_whitespace,
+            padding: None,
+            separator: &self.separator,
+        })
+    }
+
+    fn confidence(&self, s: &[u8], decoded: &[u8]) -> f64 {
+        if Self::plain(s.trim_ascii_end()) {
+            return score::weigh(score::HEX, decoded).max(score::PLAIN_HEX);
+        }
+        // Without 0x or \x, digits between separators are more likely a date,
+        // a time or a version number, whatever they decode to
+        let prefixed = s
+            .windows(2)
+            .any(|pair| pair.eq_ignore_ascii_case(b"0x") || pair == b"\\x");
+        if prefixed {
+            score::weigh(score::HEX, decoded)
+        } else {
+            score::SEPARATED_HEX
+        }
+    }
+
+    // Separated, prefixed and literal input can't be split into groups of
+    // digits, so it's read whole. Plain digits stream, spaced out or not.
+    fn decode_stream(&self, input: &mut dyn Read, output: &mut dyn Write) -> Result<(), Error> {
+        let mut start = vec![];
+        Read::take(&mut *input, stream::CHUNK_LENGTH as u64)
+            .read_to_end(&mut start)
+            .map_err(stream::read_error)?;
+        let mut input = Cursor::new(start).chain(input);
+        let start = input.get_ref().0.get_ref();
+        if start
+            .iter()
+            .all(|c| c.is_ascii_hexdigit() || c.is_ascii_whitespace())
+        {
+            return stream::copy(self, Direction::Decode, &mut input, output);
+        }
+        let mut s = vec![];
+        input.read_to_end(&mut s).map_err(stream::read_error)?;
+        output
+            .write_all(&self.decode(s)?)
+            .map_err(stream::write_error)?;
+        output.flush().map_err(stream::write_error)
     }
 }
 
+// Where the bytes are in hex input that isn't plain digits
+enum Body<'a> {
+    // Python's b"…", which has to be unescaped rather than split up
+    Quoted(&'a str),
+    // The inside of an array literal's braces or brackets
+    Array(&'a str),
+    Bare(&'a str),
+}
+
 impl HexCodec {
     fn char_to_nibble(c: char) -> Result<u8, Error> {
         Ok(match c {
-            c if c >= '0' && c <= '9' => (c as u8 - 48),
-            c if c >= 'A' && c <= 'F' => (c as u8 - 55),
-            c if c >= 'a' && c <= 'f' => (c as u8 - 87),
+            '0'..='9' => c as u8 - 48,
+            'A'..='F' => c as u8 - 55,
+            'a'..='f' => c as u8 - 87,
             _ => return Err(Error::new(format!("Invalid hex character {}", c))),
         })
     }
 
-    fn nibble_to_char(n: u8) -> char {
-        (match n & 0b00001111 {
-            v if v <= 9 => 48 + v,
-            v if v <= 15 => 87 + v,
-            _ => panic!("Hex character encoding was passed more than 4 bits"),
-        }) as char
+    // What can go between bytes besides whitespace, like --hex-separator's
+    // output. Backslashes start \x escapes.
+    fn separator(c: char) -> bool {
+        c.is_ascii_punctuation() && c != '\\'
+    }
+
+    // Nothing but digits, so not prefixed, separated or in a literal
+    fn plain(s: &[u8]) -> bool {
+        s.iter().all(u8::is_ascii_alphanumeric) && !s.starts_with(b"0x") && !s.starts_with(b"0X")
+    }
+
+    fn parse(s: &[u8]) -> Result<Vec<u8>, Error> {
+        let text = std::str::from_utf8(s)
+            .map_err(|e| Error::at("invalid utf8".to_string(), e.valid_up_to()))?;
+        let at = |part: &str| part.as_ptr() as usize - text.as_ptr() as usize;
+        let (body, array) = match Self::body(text)? {
+            Body::Quoted(body) => return Self::unescape(body, at(body)),
+            Body::Array(body) => (body, true),
+            Body::Bare(body) => (body, false),
+        };
+        let mut out = vec![];
+        for word in body.split_ascii_whitespace() {
+            let tokens = word
+                .split(Self::separator)
+                .filter(|token| !token.is_empty())
+                .collect::<Vec<_>>();
+            for token in &tokens {
+                // Java's (byte) casts
+                if array && *token == "byte" {
+                    continue;
+                }
+                let start = out.len();
+                Self::token(token, &at, &mut out)?;
+                // Spaced out digits can run on, like xxd -g2's, but ones
+                // split up by punctuation are a byte each, so a date like
+                // 2024-01-15 isn't read as hex
+                if tokens.len() > 1 && out.len() - start != 1 {
+                    return Err(Error::at(
+                        "Invalid number of characters for hex string".to_string(),
+                        at(token),
+                    ));
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    // Adds the bytes in one token of the input to out
+    fn token(token: &str, at: &dyn Fn(&str) -> usize, out: &mut Vec<u8>) -> Result<(), Error> {
+        if let Some((i, c)) = token.char_indices().find(|(_, c)| !c.is_ascii()) {
+            return Err(Error::at(
+                format!("Invalid hex character {}", c),
+                at(token) + i,
+            ));
+        }
+        match token {
+            _ if token.starts_with('\\') => {
+                // \x escapes, which may run together
+                for escape in token[1..].split('\\') {
+                    match escape.strip_prefix('x') {
+                        Some(digits) if !digits.is_empty() && digits.len() <= 2 => {
+                            out.push(Self::byte(digits, at(digits))?)
+                        }
+                        _ => {
+                            return Err(Error::at(
+                                format!("Invalid hex escape \\{}", escape),
+                                at(escape) - 1,
+                            ))
+                        }
+                    }
+                }
+            }
+            _ => {
+                let digits = match token.get(..2) {
+                    Some("0x") | Some("0X") => &token[2..],
+                    _ => token,
+                };
+                match digits.len() {
+                    // 0x5 is as good as 0x05
+                    1 if digits.len() < token.len() => out.push(Self::byte(digits, at(digits))?),
+                    n if n % 2 == 1 || n == 0 => {
+                        return Err(Error::at(
+                            "Invalid number of characters for hex string".to_string(),
+                            at(token),
+                        ))
+                    }
+                    _ => {
+                        for i in (0..digits.len()).step_by(2) {
+                            let pair = &digits[i..i + 2];
+                            out.push(Self::byte(pair, at(pair))?);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The part of the input that holds the bytes
+    fn body(text: &str) -> Result<Body<'_>, Error> {
+        let trimmed = text.trim().trim_end_matches(';').trim_end();
+        if let Some(inner) = Self::quoted(trimmed) {
+            return Ok(Body::Quoted(inner));
+        }
+        // The declaration comes before the = (or Go's :=), which has to be
+        // followed by a literal
+        let (value, declared) = match trimmed.find('=') {
+            Some(i) => (trimmed[i + 1..].trim_start(), Some(i)),
+            None => (trimmed, None),
+        };
+        if let Some(inner) = Self::quoted(value) {
+            return Ok(Body::Quoted(inner));
+        }
+        if value.starts_with('{') || value.starts_with('[') {
+            // Go's []byte comes before the braces
+            for (open, close) in [('{', '}'), ('[', ']')] {
+                if let Some(start) = value.find(open) {
+                    let end = value.rfind(close).filter(|&end| end > start);
+                    return Ok(Body::Array(&value[start + 1..end.unwrap_or(value.len())]));
+                }
+            }
+        }
+        match declared {
+            Some(i) => Err(Error::at("Invalid hex character =".to_string(), i)),
+            None => Ok(Body::Bare(value)),
+        }
+    }
+
+    // The inside of value if it's a Python bytes literal
+    fn quoted(value: &str) -> Option<&str> {
+        ['"', '\''].iter().find_map(|&quote| {
+            value
+                .strip_prefix('b')
+                .and_then(|value| value.strip_prefix(quote))
+                .and_then(|value| value.strip_suffix(quote))
+        })
+    }
+
+    // The inside of a Python bytes literal, which can have plain characters
+    // and the usual escapes as well as \x
+    fn unescape(body: &str, offset: usize) -> Result<Vec<u8>, Error> {
+        let mut out = vec![];
+        let mut chars = body.char_indices();
+        while let Some((i, c)) = chars.next() {
+            if c != '\\' {
+                let mut buf = [0; 4];
+                out.extend(c.encode_utf8(&mut buf).as_bytes());
+                continue;
+            }
+            let invalid =
+                |escape: &str| Error::at(format!("Invalid hex escape \\{}", escape), offset + i);
+            match chars.next() {
+                Some((_, 'x')) => {
+                    let digits = body.get(i + 2..i + 4).ok_or_else(|| invalid("x"))?;
+                    out.push(Self::byte(digits, offset + i + 2)?);
+                    chars.nth(1);
+                }
+                Some((_, 'n')) => out.push(b'\n'),
+                Some((_, 'r')) => out.push(b'\r'),
+                Some((_, 't')) => out.push(b'\t'),
+                Some((_, '0')) => out.push(0),
+                Some((_, c @ ('\\' | '\'' | '"'))) => out.push(c as u8),
+                Some((_, c)) => return Err(invalid(&c.to_string())),
+                None => return Err(invalid("")),
+            }
+        }
+        Ok(out)
+    }
+
+    // One or two hex digits, offset being where they are in the input
+    fn byte(digits: &str, offset: usize) -> Result<u8, Error> {
+        let mut byte = 0;
+        for (i, c) in digits.chars().enumerate() {
+            let nibble = Self::char_to_nibble(c).map_err(|e| Error::at(e.err, offset + i))?;
+            byte = (byte << 4) | nibble;
+        }
+        Ok(byte)
     }
 }
 
@@ -73,9 +410,9 @@ fn encode() {
     .cloned()
     .collect();
 
-    let codec = HexCodec {};
+    let codec = HexCodec::default();
     for (expected, bytes) in tests {
-        assert_eq!(expected, codec.encode(bytes).unwrap());
+        assert_eq!(expected.as_bytes().to_vec(), codec.encode(bytes).unwrap());
     }
 }
 
@@ -99,8 +436,128 @@ fn decode() {
     .cloned()
     .collect();
 
-    let codec = HexCodec {};
+    let codec = HexCodec::default();
     for (data, expected) in tests {
         assert_eq!(expected, codec.decode(data));
     }
 }
+
+#[test]
+fn styles() {
+    let data = vec![0xde, 0xad, 0x0b, 0xef];
+    let tests = vec![
+        (HexStyle::Plain, false, None, "dead0bef"),
+        (HexStyle::Plain, true, Some(":"), "DE:AD:0B:EF"),
+        (HexStyle::Plain, false, Some(" "), "de ad 0b ef"),
+        (HexStyle::Prefixed, false, None, "0xde, 0xad, 0x0b, 0xef"),
+        (HexStyle::Prefixed, true, Some(" "), "0xDE 0xAD 0x0B 0xEF"),
+        (HexStyle::Escaped, false, None, "\\xde\\xad\\x0b\\xef"),
+        (
+            HexStyle::C,
+            false,
+            None,
+            "uint8_t data[4] = {0xde, 0xad, 0x0b, 0xef};",
+        ),
+        (
+            HexStyle::Rust,
+            false,
+            None,
+            "let data: [u8; 4] = [0xde, 0xad, 0x0b, 0xef];",
+        ),
+        (
+            HexStyle::Python,
+            false,
+            None,
+            "data = b\"\\xde\\xad\\x0b\\xef\"",
+        ),
+        (
+            HexStyle::Go,
+            true,
+            None,
+            "data := []byte{0xDE, 0xAD, 0x0B, 0xEF}",
+        ),
+        (
+            HexStyle::Java,
+            false,
+            None,
+            "byte[] data = {(byte) 0xde, (byte) 0xad, 0x0b, (byte) 0xef};",
+        ),
+    ];
+
+    for (style, upper, separator, expected) in tests {
+        let codec = HexCodec {
+            style,
+            upper,
+            separator: separator.unwrap_or_else(|| style.separator()).to_string(),
+        };
+        assert_eq!(
+            expected.as_bytes().to_vec(),
+            codec.encode(data.clone()).unwrap()
+        );
+        // Whatever the style, decoding takes any of them
+        assert_eq!(data, HexCodec::default().decode(expected.into()).unwrap());
+    }
+
+    // Whatever goes between bytes, it decodes back
+    for separator in ["-", ";", ".", "|", " - ", "::"] {
+        for style in [HexStyle::Plain, HexStyle::Prefixed, HexStyle::Escaped] {
+            let codec = HexCodec {
+                style,
+                upper: false,
+                separator: separator.to_string(),
+            };
+            let encoded = codec.encode(data.clone()).unwrap();
+            assert_eq!(data, codec.decode(encoded).unwrap());
+        }
+    }
+}
+
+#[test]
+fn lenient() {
+    let tests = vec![
+        ("de:ad:be:ef", vec![0xde, 0xad, 0xbe, 0xef]),
+        ("0xde, 0xad", vec![0xde, 0xad]),
+        ("\\xde\\xad", vec![0xde, 0xad]),
+        ("deadbeef\n", vec![0xde, 0xad, 0xbe, 0xef]),
+        ("0xdeadbeef", vec![0xde, 0xad, 0xbe, 0xef]),
+        ("{0x1, 0x2, 0xff}", vec![0x01, 0x02, 0xff]),
+        ("b'hi\\n\\x00'", vec![b'h', b'i', b'\n', 0]),
+        ("[0xDE, 0xAD]", vec![0xde, 0xad]),
+        ("dead\nbeef\n", vec![0xde, 0xad, 0xbe, 0xef]),
+        // Grouped like xxd -g2
+        ("dead beef", vec![0xde, 0xad, 0xbe, 0xef]),
+        ("b\"a=b\"", b"a=b".to_vec()),
+    ];
+
+    let codec = HexCodec::default();
+    for (input, expected) in tests {
+        assert_eq!(expected, codec.decode(input.into()).unwrap());
+    }
+
+    let errors = vec![
+        ("de:ag", "Invalid hex character g at byte 4"),
+        (
+            "0xde, 0xa, 0xbcd",
+            "Invalid number of characters for hex string at byte 11",
+        ),
+        ("\\xde\\q1", "Invalid hex escape \\q1 at byte 4"),
+        // Separated, so a byte each
+        (
+            "12:34:5678",
+            "Invalid number of characters for hex string at byte 6",
+        ),
+        (
+            "2024-01-15",
+            "Invalid number of characters for hex string at byte 0",
+        ),
+        // Only a literal can follow an =
+        ("not hex at all =", "Invalid hex character = at byte 15"),
+        ("ab+cd/ef==", "Invalid hex character = at byte 8"),
+    ];
+    for (input, expected) in errors {
+        assert_eq!(
+            expected,
+            codec.decode(input.into()).unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use super::codec::Codec;
+use super::error::Error;
+use super::score;
+use crate::Format;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum DumpLayout {
+    // 00000000: 6865 6c6c 6f0a                           hello.
+    Xxd,
+    // 00000000  68 65 6c 6c 6f 0a                                 |hello.|
+    // Repeated lines are collapsed to a *, and the length comes last
+    Canonical,
+}
+
+// Hex dumps like xxd and hexdump -C write, with offsets, grouped bytes and
+// the printable ones alongside. Decoding ignores the offsets and gutter.
+pub struct HexdumpCodec {
+    pub layout: DumpLayout,
+    // Bytes per line
+    pub width: usize,
+    // Bytes per group; xxd runs each group together, and hexdump -C puts
+    // an extra space between them
+    pub group: usize,
+}
+
+// A line of a dump, as far as decoding cares
+struct Line {
+    offset: Option<usize>,
+    bytes: Vec<u8>,
+    // Whether it had the offset and gutter a dump would, not just hex
+    marked: bool,
+}
+
+impl Codec for HexdumpCodec {
+    fn format(&self) -> Format {
+        match self.layout {
+            DumpLayout::Xxd => Format::Xxd,
+            DumpLayout::Canonical => Format::Hexdump,
+        }
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let mut out: Vec<u8> = vec![];
+        let mut previous: Vec<u8> = vec![];
+        let mut repeating = false;
+        for line in self.lines(&s)? {
+            match line {
+                // A * stands for copies of the line before it, up to the
+                // offset of the line after
+                None => repeating = true,
+                Some(line) => {
+                    if let (true, Some(offset)) = (repeating, line.offset) {
+                        if !previous.is_empty() {
+                            let copies = previous.iter().cycle();
+                            let missing = offset.saturating_sub(out.len());
+                            out.extend(copies.take(missing).collect::<Vec<_>>());
+                        }
+                    }
+                    repeating = false;
+                    if !line.bytes.is_empty() {
+                        out.extend(&line.bytes);
+                        previous = line.bytes;
+                    }
+                }
+            }
+        }
+        if out.is_empty() && !s.iter().all(u8::is_ascii_whitespace) {
+            return Err(Error::new(format!("No data in {}", self.format())));
+        }
+        Ok(out)
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        if self.width == 0 || self.group == 0 {
+            return Err(Error::new(format!(
+                "{} needs at least one byte per line and group",
+                self.format()
+            )));
+        }
+        let mut out = String::new();
+        let mut previous: Option<&[u8]> = None;
+        let mut collapsed = false;
+        for (i, line) in data.chunks(self.width).enumerate() {
+            let offset = i * self.width;
+            match self.layout {
+                DumpLayout::Xxd => {
+                    let hex = line
+                        .chunks(self.group)
+                        .map(|group| group.iter().map(|b| format!("{:02x}", b)).collect())
+                        .collect::<Vec<String>>()
+                        .join(" ");
+                    let groups = self.width.div_ceil(self.group);
+                    let area = self.width * 2 + groups - 1;
+                    out += &format!("{:08x}: {:area$}  {}\n", offset, hex, gutter(line));
+                }
+                DumpLayout::Canonical => {
+                    if previous == Some(line) && line.len() == self.width {
+                        if !collapsed {
+                            out += "*\n";
+                            collapsed = true;
+                        }
+                        continue;
+                    }
+                    previous = Some(line);
+                    collapsed = false;
+                    let hex = (0..self.width)
+                        .map(|j| {
+                            let space = if j > 0 && j % self.group == 0 {
+                                " "
+                            } else {
+                                ""
+                            };
+                            match line.get(j) {
+                                Some(b) => format!("{}{:02x} ", space, b),
+                                None => format!("{}   ", space),
+                            }
+                        })
+                        .collect::<String>();
+                    out += &format!("{:08x}  {} |{}|\n", offset, hex, gutter(line));
+                }
+            }
+        }
+        if self.layout == DumpLayout::Canonical && !data.is_empty() {
+            out += &format!("{:08x}\n", data.len());
+        }
+        Ok(out.into_bytes())
+    }
+
+    fn confidence(&self, s: &[u8], decoded: &[u8]) -> f64 {
+        // Plain hex decodes as a dump too, but without offsets and gutters
+        // it's better taken as hex
+        let lines = self
+            .lines(s)
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .filter(|line| !line.bytes.is_empty())
+            .collect::<Vec<_>>();
+        let marked = lines.iter().filter(|line| line.marked).count();
+        let fit = if lines.is_empty() {
+            0.0
+        } else {
+            0.95 * marked as f64 / lines.len() as f64
+        };
+        score::weigh(fit, decoded)
+    }
+}
+
+impl HexdumpCodec {
+    // Each line of the dump, or None for a * line
+    fn lines(&self, s: &[u8]) -> Result<Vec<Option<Line>>, Error> {
+        let text = std::str::from_utf8(s)
+            .map_err(|e| Error::at("invalid utf8".to_string(), e.valid_up_to()))?;
+        let at = |part: &str| part.as_ptr() as usize - text.as_ptr() as usize;
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                if line.trim() == "*" {
+                    return Ok(None);
+                }
+                let line = line.trim();
+                let (offset, rest) = match self.layout {
+                    DumpLayout::Xxd => match line.split_once(':') {
+                        Some((offset, rest)) => (Some(offset), rest.trim_start()),
+                        None => (None, line),
+                    },
+                    DumpLayout::Canonical => {
+                        let end = line.find(char::is_whitespace).unwrap_or(line.len());
+                        (Some(&line[..end]), line[end..].trim_start())
+                    }
+                };
+                let offset = offset
+                    .map(|offset| {
+                        usize::from_str_radix(offset.trim(), 16).map_err(|_| {
+                            Error::at(format!("Invalid offset in {}", self.format()), at(offset))
+                        })
+                    })
+                    .transpose()?;
+                // The gutter starts after a double space in xxd, and at the
+                // first | in hexdump -C
+                let (hex, gutter) = match self.layout {
+                    DumpLayout::Xxd => match rest.find("  ") {
+                        Some(end) => (&rest[..end], true),
+                        None => (rest, false),
+                    },
+                    DumpLayout::Canonical => match rest.find('|') {
+                        Some(end) => (&rest[..end], true),
+                        None => (rest, false),
+                    },
+                };
+                let bytes = hex
+                    .split_whitespace()
+                    .map(|token| {
+                        if token.len() % 2 == 1 || !token.bytes().all(|c| c.is_ascii_hexdigit()) {
+                            return Err(Error::at(
+                                format!("Invalid hex {} in {}", token, self.format()),
+                                at(token),
+                            ));
+                        }
+                        Ok((0..token.len())
+                            .step_by(2)
+                            // Safe since every character is a hex digit
+                            .map(|i| u8::from_str_radix(&token[i..i + 2], 16).unwrap())
+                            .collect::<Vec<_>>())
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?
+                    .concat();
+                Ok(Some(Line {
+                    marked: offset.is_some() && (gutter || bytes.is_empty()),
+                    offset,
+                    bytes,
+                }))
+            })
+            .collect()
+    }
+}
+
+// The bytes as ASCII, with a dot for each that isn't printable
+fn gutter(line: &[u8]) -> String {
+    line.iter()
+        .map(|&b| match b {
+            0x20..=0x7e => b as char,
+            _ => '.',
+        })
+        .collect()
+}
+
+#[test]
+fn encode() {
+    let data = b"hello world, this is a test!\n\x00\x01".to_vec();
+    let tests = vec![
+        (
+            DumpLayout::Xxd,
+            16,
+            2,
+            "00000000: 6865 6c6c 6f20 776f 726c 642c 2074 6869  hello world, thi\n\
+             00000010: 7320 6973 2061 2074 6573 7421 0a00 01    s is a test!...\n",
+        ),
+        (
+            DumpLayout::Xxd,
+            8,
+            4,
+            "00000000: 68656c6c 6f20776f  hello wo\n\
+             00000008: 726c642c 20746869  rld, thi\n\
+             00000010: 73206973 20612074  s is a t\n\
+             00000018: 65737421 0a0001    est!...\n",
+        ),
+        (
+            DumpLayout::Canonical,
+            16,
+            8,
+            "00000000  68 65 6c 6c 6f 20 77 6f  72 6c 64 2c 20 74 68 69  |hello world, thi|\n\
+             00000010  73 20 69 73 20 61 20 74  65 73 74 21 0a 00 01     |s is a test!...|\n\
+             0000001f\n",
+        ),
+    ];
+
+    for (layout, width, group, dump) in tests {
+        let codec = HexdumpCodec {
+            layout,
+            width,
+            group,
+        };
+        assert_eq!(
+            dump,
+            String::from_utf8(codec.encode(data.clone()).unwrap()).unwrap()
+        );
+        assert_eq!(data, codec.decode(dump.as_bytes().to_vec()).unwrap());
+    }
+}
+
+#[test]
+fn decode() {
+    let canonical = HexdumpCodec {
+        layout: DumpLayout::Canonical,
+        width: 16,
+        group: 8,
+    };
+    let zeros = vec![0; 40];
+    let dump = canonical.encode(zeros.clone()).unwrap();
+    assert_eq!(
+        "00000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+         *\n\
+         00000020  00 00 00 00 00 00 00 00                           |........|\n\
+         00000028\n",
+        String::from_utf8(dump.clone()).unwrap()
+    );
+    assert_eq!(zeros, canonical.decode(dump).unwrap());
+
+    // Pasted from a ticket, with the gutter full of hex digits
+    let xxd = HexdumpCodec {
+        layout: DumpLayout::Xxd,
+        width: 16,
+        group: 2,
+    };
+    assert_eq!(
+        b"cafe babe".to_vec(),
+        xxd.decode(b"  00000000: 6361 6665 2062 6162 65  cafe babe\r\n".to_vec())
+            .unwrap()
+    );
+    assert_eq!(
+        "Invalid hex 6g65 in xxd at byte 15",
+        xxd.decode(b"00000000: 6361 6g65  ca.e".to_vec())
+            .unwrap_err()
+            .to_string()
+    );
+}
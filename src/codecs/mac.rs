@@ -0,0 +1,144 @@
+use super::codec::Codec;
+use super::digest::DigestText;
+use super::error::Error;
+use crate::Format;
+use hmac::{Hmac, Mac};
+use subtle::ConstantTimeEq;
+
+// The hashes HMAC is offered over
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum MacAlgorithm {
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+pub struct HmacCodec {
+    pub algorithm: MacAlgorithm,
+    // None until a key is given, so the format can be listed but not used
+    pub key: Option<Vec<u8>>,
+    pub text: DigestText,
+}
+
+impl Codec for HmacCodec {
+    fn format(&self) -> Format {
+        match self.algorithm {
+            MacAlgorithm::Md5 => Format::HmacMd5,
+            MacAlgorithm::Sha1 => Format::HmacSha1,
+            MacAlgorithm::Sha224 => Format::HmacSha224,
+            MacAlgorithm::Sha256 => Format::HmacSha256,
+            MacAlgorithm::Sha384 => Format::HmacSha384,
+            MacAlgorithm::Sha512 => Format::HmacSha512,
+        }
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        match &self.key {
+            Some(key) => self.text.write(self.mac(key, &data)),
+            None => Err(Error::new(format!(
+                "No key given for {}; use --key",
+                self.format()
+            ))),
+        }
+    }
+
+    fn decodable(&self) -> bool {
+        false
+    }
+}
+
+impl HmacCodec {
+    pub fn mac(&self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        // HMAC takes keys of any length, so new_from_slice can't fail
+        fn run<M: Mac + hmac::digest::KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+            let mut mac = <M as Mac>::new_from_slice(key).unwrap();
+            mac.update(data);
+            mac.finalize().into_bytes().to_vec()
+        }
+        match self.algorithm {
+            MacAlgorithm::Md5 => run::<Hmac<md5::Md5>>(key, data),
+            MacAlgorithm::Sha1 => run::<Hmac<sha1::Sha1>>(key, data),
+            MacAlgorithm::Sha224 => run::<Hmac<sha2::Sha224>>(key, data),
+            MacAlgorithm::Sha256 => run::<Hmac<sha2::Sha256>>(key, data),
+            MacAlgorithm::Sha384 => run::<Hmac<sha2::Sha384>>(key, data),
+            MacAlgorithm::Sha512 => run::<Hmac<sha2::Sha512>>(key, data),
+        }
+    }
+}
+
+impl MacAlgorithm {
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::Md5,
+            Self::Sha1,
+            Self::Sha224,
+            Self::Sha256,
+            Self::Sha384,
+            Self::Sha512,
+        ]
+    }
+}
+
+// Compares MACs in constant time, so a mismatch doesn't leak how much
+// of the expected value was right
+pub fn verify(expected: &[u8], actual: &[u8]) -> bool {
+    expected.ct_eq(actual).into()
+}
+
+#[test]
+fn encode() {
+    use std::collections::HashMap;
+
+    // RFC 2202 and RFC 4231 test case 2
+    let tests: HashMap<MacAlgorithm, &str> = [
+        (MacAlgorithm::Md5, "750c783e6ab0b503eaa86e310a5db738"),
+        (MacAlgorithm::Sha1, "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"),
+        (
+            MacAlgorithm::Sha224,
+            "a30e01098bc6dbbf45690f3a7e9e6d0f8bbea2a39e6148008fd05e44",
+        ),
+        (
+            MacAlgorithm::Sha256,
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        ),
+        (
+            MacAlgorithm::Sha384,
+            "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649",
+        ),
+        (
+            MacAlgorithm::Sha512,
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+        ),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    for (algorithm, expected) in tests {
+        let codec = HmacCodec {
+            algorithm,
+            key: Some(b"Jefe".to_vec()),
+            text: DigestText::Hex,
+        };
+        assert_eq!(
+            expected.as_bytes().to_vec(),
+            codec
+                .encode(b"what do ya want for nothing?".to_vec())
+                .unwrap()
+        );
+    }
+
+    let keyless = HmacCodec {
+        algorithm: MacAlgorithm::Sha256,
+        key: None,
+        text: DigestText::Hex,
+    };
+    assert!(keyless.encode(b"abc".to_vec()).is_err());
+
+    assert!(verify(b"abc", b"abc"));
+    assert!(!verify(b"abc", b"abd"));
+    assert!(!verify(b"abc", b"ab"));
+}
@@ -1,9 +1,25 @@
+pub mod ascii85;
+pub mod base32;
 pub mod base64;
 pub mod binary;
+pub mod charset;
+pub mod checksum;
+pub mod cipher;
+pub mod cjk;
 pub mod codec;
+pub mod digest;
 pub mod error;
+pub mod escape;
+pub mod gzip;
 pub mod hex;
+pub mod hexdump;
+pub mod mac;
+pub mod number;
 pub mod raw;
-pub mod rot13;
+pub mod score;
 pub mod spelling;
+pub mod stream;
+pub mod unicode;
+pub mod url;
 pub mod utf8;
+pub mod xor;
@@ -0,0 +1,156 @@
+use super::codec::Codec;
+use super::error::Error;
+use crate::Format;
+
+pub struct NumberCodec {
+    // Between 2 and 36, so every digit is an ascii alphanumeric
+    pub radix: u32,
+}
+
+impl Codec for NumberCodec {
+    fn format(&self) -> Format {
+        Format::from_radix(self.radix)
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let s = String::from_utf8(s)
+            .map_err(|_| Error::new(format!("Input to {} was invalid utf8", self.format())))?;
+        let s = s.trim();
+        let s = self.strip_prefix(s).replace('_', "");
+        if s.is_empty() {
+            return Err(Error::new("Empty number".to_string()));
+        }
+
+        // Big-endian bytes of the value so far
+        let mut bytes: Vec<u8> = vec![0];
+        for c in s.chars() {
+            let digit = c
+                .to_digit(self.radix)
+                .ok_or_else(|| Error::new(format!("Invalid digit {} for {}", c, self.format())))?;
+            Self::mul_add(&mut bytes, self.radix, digit);
+        }
+        Ok(bytes)
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let mut bytes = data;
+        let mut digits = vec![];
+        while bytes.iter().any(|&b| b != 0) {
+            let digit = Self::div_rem(&mut bytes, self.radix);
+            // Safe since the remainder is always less than the radix
+            digits.push(std::char::from_digit(digit, self.radix).unwrap());
+        }
+        if digits.is_empty() {
+            digits.push('0');
+        }
+        Ok(digits.into_iter().rev().collect::<String>().into_bytes())
+    }
+
+    fn inferrable(&self) -> bool {
+        // Almost any number is also valid hex
+        false
+    }
+}
+
+impl NumberCodec {
+    fn strip_prefix<'a>(&self, s: &'a str) -> &'a str {
+        let prefix = match self.radix {
+            2 => ["0b", "0B"],
+            8 => ["0o", "0O"],
+            16 => ["0x", "0X"],
+            _ => return s,
+        };
+        prefix.iter().find_map(|p| s.strip_prefix(p)).unwrap_or(s)
+    }
+
+    // bytes = bytes * radix + digit, growing bytes as needed
+    fn mul_add(bytes: &mut Vec<u8>, radix: u32, digit: u32) {
+        let mut carry = digit;
+        for b in bytes.iter_mut().rev() {
+            let v = *b as u32 * radix + carry;
+            *b = v as u8;
+            carry = v >> 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    // bytes = bytes / radix, returning the remainder
+    fn div_rem(bytes: &mut [u8], radix: u32) -> u32 {
+        let mut rem = 0;
+        for b in bytes.iter_mut() {
+            let v = (rem << 8) | *b as u32;
+            *b = (v / radix) as u8;
+            rem = v % radix;
+        }
+        rem
+    }
+}
+
+#[test]
+fn encode() {
+    use std::collections::HashMap;
+
+    let tests: HashMap<(&str, u32), Vec<u8>> = [
+        (("0", 10), vec![]),
+        (("0", 2), vec![0, 0]),
+        (("255", 10), vec![0xff]),
+        (("11111111", 2), vec![0xff]),
+        (("377", 8), vec![0xff]),
+        (("ff", 16), vec![0xff]),
+        (("73", 36), vec![0xff]),
+        (
+            ("12345678901234567890", 10),
+            vec![0xab, 0x54, 0xa9, 0x8c, 0xeb, 0x1f, 0x0a, 0xd2],
+        ),
+        (("10000", 16), vec![0x00, 0x01, 0x00, 0x00]),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    for ((expected, radix), bytes) in tests {
+        let codec = NumberCodec { radix };
+        assert_eq!(expected.as_bytes().to_vec(), codec.encode(bytes).unwrap());
+    }
+}
+
+#[test]
+fn decode() {
+    use std::collections::HashMap;
+
+    let tests: HashMap<(&str, u32), Result<Vec<u8>, Error>> = [
+        (("0", 10), Ok(vec![0])),
+        (
+            ("12345678901234567890", 10),
+            Ok(vec![0xab, 0x54, 0xa9, 0x8c, 0xeb, 0x1f, 0x0a, 0xd2]),
+        ),
+        (
+            ("12_345_678_901_234_567_890", 10),
+            Ok(vec![0xab, 0x54, 0xa9, 0x8c, 0xeb, 0x1f, 0x0a, 0xd2]),
+        ),
+        (("0xFF_FF", 16), Ok(vec![0xff, 0xff])),
+        (("0b1_0000_0000", 2), Ok(vec![0x01, 0x00])),
+        (("0o777", 8), Ok(vec![0x01, 0xff])),
+        (("zz", 36), Ok(vec![0x05, 0x0f])),
+        (("0x", 16), Err(Error::new("Empty number".to_string()))),
+        (
+            ("0x1f", 10),
+            Err(Error::new("Invalid digit x for base 10".to_string())),
+        ),
+        (
+            ("102", 2),
+            Err(Error::new("Invalid digit 2 for base 2".to_string())),
+        ),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    for ((data, radix), expected) in tests {
+        let codec = NumberCodec { radix };
+        assert_eq!(expected, codec.decode(data.as_bytes().to_vec()));
+    }
+}
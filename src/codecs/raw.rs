@@ -1,5 +1,6 @@
-use super::codec::Codec;
+use super::codec::{Codec, Framing};
 use super::error::Error;
+use super::score;
 use crate::Format;
 
 pub struct RawCodec {}
@@ -13,8 +14,26 @@ impl Codec for RawCodec {
         Ok(s)
     }
 
-    // TODO this aint it
-    fn encode(&self, data: Vec<u8>) -> Result<String, Error> {
-        Ok(data.into_iter().map(|v| v as char).collect())
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        Ok(data)
+    }
+
+    fn printable(&self) -> bool {
+        false
+    }
+
+    fn framing(&self) -> Option<Framing<'_>> {
+        Some(Framing {
+            symbols: 1,
+            bytes: 1,
+            ignored: |_| false,
+            padding: None,
+            separator: "",
+        })
+    }
+
+    fn confidence(&self, _s: &[u8], _decoded: &[u8]) -> f64 {
+        // Raw bytes are the last resort, when nothing else decodes
+        score::RAW
     }
 }
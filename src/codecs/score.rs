@@ -0,0 +1,161 @@
+// Scores data from 0 to 1 by how much it looks like text a person would write.
+// Lowercase letters and spaces count most, so that intermediate encodings
+// like hex digits or base 64 score lower than whatever they decode to.
+pub fn readability(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    match std::str::from_utf8(data) {
+        Ok(s) => {
+            let (total, count) = s.chars().fold((0.0, 0), |(total, count), c| {
+                let score = match c {
+                    c if c.is_lowercase() || c == ' ' => 1.0,
+                    c if c.is_uppercase() => 0.8,
+                    '\n' | '\r' | '\t' => 0.8,
+                    c if c.is_alphabetic() => 0.7,
+                    c if c.is_ascii_digit() || c.is_ascii_punctuation() => 0.5,
+                    c if c.is_control() => 0.0,
+                    _ => 0.3,
+                };
+                (total + score, count + 1)
+            });
+            total / count as f64
+        }
+        // Binary data can be at most half as readable as text
+        Err(_) => {
+            let printable = data
+                .iter()
+                .filter(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+                .count();
+            0.5 * printable as f64 / data.len() as f64
+        }
+    }
+}
+
+// Combines how unlikely it is for arbitrary text to fit a format's alphabet
+// and length rules with how plausible the decoded output is
+pub fn weigh(fit: f64, decoded: &[u8]) -> f64 {
+    (fit + readability(decoded)) / 2.0
+}
+
+// Fits for the formats whose alphabets overlap with ordinary text. Anything
+// is utf8, so utf8's fit is 0 and it scores at most 0.5 on readable text.
+// Another format beats it when its fit plus the readability of what it
+// decodes to comes to more than the readability of the input itself.
+
+// 16 of the 95 printable characters, in pairs. Short words like "cafe" fit,
+// but little else a person types does, so hex output that reads well wins
+// by a wide margin.
+pub const HEX: f64 = 0.8;
+// An even-length run of hex digits is an ID, hash or key far more often than
+// a word spelled from a to f, so it's taken as hex whatever it decodes to.
+// That's above utf8's best, and above base 32's best on binary output.
+pub const PLAIN_HEX: f64 = 0.6;
+// Digits between separators with no 0x or \x could as well be a date, a time
+// or a version number, so this is hex.rs's whole confidence, not a fit. Input
+// made of digits and punctuation is at least half readable, so utf8 scores at
+// least 0.25 on it, and ranks above.
+pub const SEPARATED_HEX: f64 = 0.1;
+// 32 uppercase symbols, padded to a multiple of 8. Uppercase words fit the
+// alphabet but not the length, and base32.rs only uses this when both fit.
+pub const BASE32: f64 = 0.65;
+// Base 64 covers most of what people type, and a length that's a multiple
+// of 4 is a one-in-four chance, so only trailing padding says much
+pub const BASE64_PADDED: f64 = 0.6;
+pub const BASE64: f64 = 0.35;
+// Unpadded and url-safe variants accept more lengths still, so they rank
+// just below the standard alphabet when both decode
+pub const BASE64_LOOSE: f64 = 0.3;
+// Raw bytes fit anything, and they're only the answer when nothing else
+// decodes, so they get a flat low score rather than being weighed
+pub const RAW: f64 = 0.1;
+
+// How often each letter turns up in English text, a to z
+const ENGLISH: [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966, 0.00153,
+    0.00772, 0.04025, 0.02406, 0.06749, 0.07507, 0.01929, 0.00095, 0.05987, 0.06327, 0.09056,
+    0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+// Counts each letter in data, ignoring case and everything that isn't a to z
+pub fn letter_counts(data: &[u8]) -> [usize; 26] {
+    let mut counts = [0; 26];
+    data.iter()
+        .filter(|c| c.is_ascii_alphabetic())
+        .for_each(|c| counts[(c.to_ascii_lowercase() - b'a') as usize] += 1);
+    counts
+}
+
+// Chi-squared distance of data's letter frequencies from English's. Lower is
+// more like English, and data without letters is infinitely far away.
+pub fn english(data: &[u8]) -> f64 {
+    let counts = letter_counts(data);
+    let total = counts.iter().sum::<usize>() as f64;
+    if total == 0.0 {
+        return f64::INFINITY;
+    }
+    counts
+        .iter()
+        .zip(ENGLISH.iter())
+        .map(|(&count, freq)| {
+            let expected = freq * total;
+            (count as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+// Roughly how much of English prose is spaces, and other printable ASCII
+// like punctuation and digits. Letters make up the rest, less the odd byte
+// that isn't text at all.
+const SPACES: f64 = 0.18;
+const OTHER: f64 = 0.02;
+const NON_TEXT: f64 = 0.0001;
+
+// Like english, but for data that may not be text at all, so spaces,
+// punctuation and bytes that aren't text count too
+pub fn english_text(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return f64::INFINITY;
+    }
+    let letters = letter_counts(data);
+    let spaces = data.iter().filter(|&&b| b == b' ').count();
+    let other = data
+        .iter()
+        .filter(|b| {
+            (b.is_ascii_graphic() && !b.is_ascii_alphabetic()) || matches!(b, b'\t' | b'\n' | b'\r')
+        })
+        .count();
+    let non_text = data.len() - letters.iter().sum::<usize>() - spaces - other;
+    let letter_share = 1.0 - SPACES - OTHER - NON_TEXT;
+    let total = data.len() as f64;
+    letters
+        .iter()
+        .zip(ENGLISH.iter().map(|freq| freq * letter_share))
+        .chain(vec![
+            (&spaces, SPACES),
+            (&other, OTHER),
+            (&non_text, NON_TEXT),
+        ])
+        .map(|(&count, share)| {
+            let expected = share * total;
+            (count as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+#[test]
+fn readable() {
+    assert!(readability(b"hello world") > readability(b"aGVsbG8gd29ybGQ="));
+    assert!(readability(b"aGVsbG8gd29ybGQ=") > readability(b"68656c6c6f"));
+    assert!(readability(b"68656c6c6f") > readability(&[0x1f, 0x8b, 0x08, 0x00]));
+}
+
+#[test]
+fn englishness() {
+    assert!(english(b"it was the best of times") < english(b"vg jnf gur orfg bs gvzrf"));
+    assert!(english(b"meet me at noon") < english(b"xqjz kw vf pbby"));
+    assert_eq!(f64::INFINITY, english(b"1234"));
+    assert!(english_text(b"it was the best") < english_text(b"it\x00was\x00the\x00best"));
+    assert!(english_text(b"it was the best") < english_text(b"IT\x7fWAS\x7fTHE\x7fBEST"));
+    assert!(english_text(b"it was the best") < english_text(b"i}t;w,a+s*t(h"));
+}
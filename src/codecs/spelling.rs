@@ -1,5 +1,6 @@
 use super::codec::Codec;
 use super::error::Error;
+use super::score;
 use crate::Format;
 
 pub struct SpellingCodec {}
@@ -21,7 +22,7 @@ impl Codec for SpellingCodec {
             .map(|s| s.into_bytes())
     }
 
-    fn encode(&self, data: Vec<u8>) -> Result<String, Error> {
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
         match String::from_utf8(data.clone()) {
             Ok(s)
                 if s.chars()
@@ -37,12 +38,17 @@ impl Codec for SpellingCodec {
                             .join(" ")
                     })
                     .collect::<Vec<String>>()
-                    .join(" "))
+                    .join(" ")
+                    .into_bytes())
             }
             Ok(_) => Err(Error::new("input data is not ascii".to_string())),
             Err(_) => Err(Error::new("input data is not utf8".to_string())),
         }
     }
+
+    fn confidence(&self, _s: &[u8], decoded: &[u8]) -> f64 {
+        score::weigh(0.9, decoded)
+    }
 }
 
 impl SpellingCodec {
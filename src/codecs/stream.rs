@@ -0,0 +1,333 @@
+use super::codec::{Codec, Framing};
+use super::error::Error;
+use std::io;
+use std::io::{Read, Write};
+
+// How much input to read at a time
+pub const CHUNK_LENGTH: usize = 64 * 1024;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Direction {
+    Decode,
+    Encode,
+}
+
+// Feeds a codec its input a piece at a time. Anything that doesn't fill a
+// whole group is held back until more input arrives or the input ends.
+// Codecs without framing get all of their input at once when it ends, so
+// they hold the whole input in memory.
+pub struct Chunker<'a, C: Codec + ?Sized> {
+    codec: &'a C,
+    direction: Direction,
+    framing: Option<Framing<'a>>,
+    pending: Vec<u8>,
+    // Where each pending byte was in the input, once ignored bytes are
+    // taken out when decoding
+    positions: Vec<usize>,
+    // Input already read and already converted, for error offsets
+    read: usize,
+    done: usize,
+    // Whether trailing whitespace is left off, like the newline ending
+    // most piped text
+    trim: bool,
+    started: bool,
+}
+
+impl<'a, C: Codec + ?Sized> Chunker<'a, C> {
+    pub fn new(codec: &'a C, direction: Direction) -> Self {
+        let framing = codec.framing();
+        Self {
+            codec,
+            direction,
+            // Only text split into groups, where whitespace isn't data
+            trim: direction == Direction::Decode && framing.is_some() && codec.printable(),
+            framing,
+            pending: vec![],
+            positions: vec![],
+            read: 0,
+            done: 0,
+            started: false,
+        }
+    }
+
+    pub fn push(&mut self, data: &[u8], out: &mut dyn Write) -> Result<(), Error> {
+        match (&self.framing, self.direction) {
+            (Some(framing), Direction::Decode) => {
+                for (i, c) in data.iter().enumerate() {
+                    if !(framing.ignored)(c) {
+                        self.pending.push(*c);
+                        self.positions.push(self.read + i);
+                    }
+                }
+            }
+            _ => self.pending.extend_from_slice(data),
+        }
+        self.read += data.len();
+        let group = match (&self.framing, self.direction) {
+            (None, _) => return Ok(()),
+            (Some(framing), Direction::Decode) => framing.symbols,
+            (Some(framing), Direction::Encode) => framing.bytes,
+        };
+        // Whitespace that might turn out to end the input waits for more
+        let mut length = match self.trim {
+            true => self.trimmed(),
+            false => self.pending.len(),
+        };
+        // Padding has to end the input, so its group waits for the end, and
+        // anything after that group is an error as it would be all at once
+        let padding = match (&self.framing, self.direction) {
+            (Some(framing), Direction::Decode) => framing.padding,
+            _ => None,
+        };
+        if let Some(padding) = padding {
+            if let Some(i) = self.pending[..length].iter().position(|&c| c == padding) {
+                if length > i - i % group + group {
+                    return Err(Error::at(
+                        "Non-tailing padding".to_string(),
+                        self.positions[i],
+                    ));
+                }
+                length = i - i % group;
+            }
+        }
+        let whole = length - length % group;
+        if whole > 0 {
+            let rest = self.pending.split_off(whole);
+            let ready = std::mem::replace(&mut self.pending, rest);
+            self.convert(ready, out)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self, out: &mut dyn Write) -> Result<(), Error> {
+        // Empty input still converts to something, like "0" in a number base
+        if self.trim {
+            let length = self.trimmed();
+            self.pending.truncate(length);
+            self.positions.truncate(length);
+        }
+        if !self.pending.is_empty() || !self.started {
+            let last = std::mem::take(&mut self.pending);
+            self.convert(last, out)?;
+        }
+        out.flush().map_err(write_error)
+    }
+
+    // Length of pending without trailing whitespace
+    fn trimmed(&self) -> usize {
+        self.pending
+            .iter()
+            .rposition(|c| !c.is_ascii_whitespace())
+            .map_or(0, |i| i + 1)
+    }
+
+    fn convert(&mut self, data: Vec<u8>, out: &mut dyn Write) -> Result<(), Error> {
+        let len = data.len();
+        let rest = self.positions.split_off(len.min(self.positions.len()));
+        let positions = std::mem::replace(&mut self.positions, rest);
+        let converted = match self.direction {
+            Direction::Decode => self.codec.decode(data),
+            Direction::Encode => self.codec.encode(data),
+        }
+        .map_err(|e| Error {
+            offset: e.offset.map(|offset| match positions.last() {
+                // Past the last byte is just after it in the input
+                Some(last) => positions.get(offset).copied().unwrap_or(last + 1),
+                None => offset + self.done,
+            }),
+            err: e.err,
+        })?;
+        if self.started && self.direction == Direction::Encode {
+            if let Some(framing) = &self.framing {
+                out.write_all(framing.separator.as_bytes())
+                    .map_err(write_error)?;
+            }
+        }
+        out.write_all(&converted).map_err(write_error)?;
+        self.done += len;
+        self.started = true;
+        Ok(())
+    }
+}
+
+// Lets a decoder write straight into an encoder
+pub struct EncodeWriter<'a, C: Codec + ?Sized> {
+    chunker: Chunker<'a, C>,
+    out: &'a mut dyn Write,
+    // io::Error can't carry our errors intact, so they wait here
+    failed: Option<Error>,
+}
+
+impl<'a, C: Codec + ?Sized> EncodeWriter<'a, C> {
+    pub fn new(codec: &'a C, out: &'a mut dyn Write) -> Self {
+        Self {
+            chunker: Chunker::new(codec, Direction::Encode),
+            out,
+            failed: None,
+        }
+    }
+
+    // Encodes whatever is left, or gives back the error that stopped the
+    // writer partway
+    pub fn finish(self, result: Result<(), Error>) -> Result<(), Error> {
+        match (self.failed, result) {
+            (Some(e), _) | (None, Err(e)) => Err(e),
+            (None, Ok(())) => self.chunker.finish(self.out),
+        }
+    }
+}
+
+impl<'a, C: Codec + ?Sized> Write for EncodeWriter<'a, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.chunker.push(buf, self.out) {
+            Ok(()) => Ok(buf.len()),
+            Err(e) => {
+                let message = e.to_string();
+                self.failed = Some(e);
+                Err(io::Error::other(message))
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+// Converts all of input into output
+pub fn copy<C: Codec + ?Sized>(
+    codec: &C,
+    direction: Direction,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+) -> Result<(), Error> {
+    let mut chunker = Chunker::new(codec, direction);
+    let mut buf = vec![0; CHUNK_LENGTH];
+    loop {
+        match input.read(&mut buf) {
+            Ok(0) => return chunker.finish(output),
+            Ok(n) => chunker.push(&buf[..n], output)?,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(read_error(e)),
+        }
+    }
+}
+
+// The longest start of prefix that holds only whole groups, so it decodes
+// even when the input it came from was cut off partway through a group
+pub fn aligned<'a>(prefix: &'a [u8], framing: &Framing) -> &'a [u8] {
+    let mut symbols = 0;
+    let mut end = 0;
+    for (i, c) in prefix.iter().enumerate() {
+        if !(framing.ignored)(c) {
+            symbols += 1;
+            if symbols % framing.symbols == 0 {
+                end = i + 1;
+            }
+        }
+    }
+    &prefix[..end]
+}
+
+pub fn read_error(e: io::Error) -> Error {
+    Error::new(format!("Couldn't read input: {}", e))
+}
+
+pub fn write_error(e: io::Error) -> Error {
+    Error::new(format!("Couldn't write output: {}", e))
+}
+
+#[test]
+fn chunked() {
+    use super::base32::{Base32Codec, Base32Variant};
+    use super::base64::{Base64Codec, Base64Variant};
+    use super::binary::BinaryCodec;
+    use super::hex::HexCodec;
+
+    let data = (0..=255).cycle().take(1000).collect::<Vec<u8>>();
+    let codecs: Vec<Box<dyn Codec>> = vec![
+        Box::new(HexCodec::default()),
+        Box::new(BinaryCodec {}),
+        Box::new(Base32Codec {
+            variant: Base32Variant::Standard,
+        }),
+        Box::new(Base32Codec {
+            variant: Base32Variant::CrockfordCheck,
+        }),
+        Box::new(Base64Codec {
+            variant: Base64Variant::Unpadded,
+        }),
+        Box::new(Base64Codec {
+            variant: Base64Variant::Mime,
+        }),
+    ];
+
+    // Odd piece sizes so groups get split across pushes
+    for codec in codecs {
+        let whole = codec.encode(data.clone()).unwrap();
+        for size in [1, 7, 64] {
+            let mut encoded = vec![];
+            let mut chunker = Chunker::new(codec.as_ref(), Direction::Encode);
+            data.chunks(size)
+                .for_each(|piece| chunker.push(piece, &mut encoded).unwrap());
+            chunker.finish(&mut encoded).unwrap();
+            assert_eq!(whole, encoded);
+
+            let mut decoded = vec![];
+            let mut chunker = Chunker::new(codec.as_ref(), Direction::Decode);
+            encoded
+                .chunks(size)
+                .for_each(|piece| chunker.push(piece, &mut decoded).unwrap());
+            chunker.finish(&mut decoded).unwrap();
+            assert_eq!(data, decoded);
+        }
+    }
+
+    // A trailing newline isn't data, and errors point into the input as given
+    let codec = Base64Codec {
+        variant: Base64Variant::Mime,
+    };
+    for size in [1, 3, 64] {
+        let input = b"aGVs\r\nbG8=\n";
+        let mut decoded = vec![];
+        let mut chunker = Chunker::new(&codec, Direction::Decode);
+        input
+            .chunks(size)
+            .for_each(|piece| chunker.push(piece, &mut decoded).unwrap());
+        chunker.finish(&mut decoded).unwrap();
+        assert_eq!(b"hello".to_vec(), decoded);
+    }
+    // Padding can't be followed by more groups, however the input is split
+    let codec = Base64Codec {
+        variant: Base64Variant::Standard,
+    };
+    for size in [1, 4, 64] {
+        let mut decoded = vec![];
+        let mut chunker = Chunker::new(&codec, Direction::Decode);
+        let failed = b"aGk=aGk="
+            .chunks(size)
+            .try_for_each(|piece| chunker.push(piece, &mut decoded))
+            .and_then(|()| chunker.finish(&mut decoded));
+        assert_eq!(
+            "Non-tailing padding at byte 3",
+            failed.unwrap_err().to_string()
+        );
+    }
+
+    let codec = HexCodec::default();
+    let mut chunker = Chunker::new(&codec, Direction::Decode);
+    let mut decoded = vec![];
+    let failed = chunker
+        .push(b"\\xde\n\\xzz\n", &mut decoded)
+        .and_then(|()| chunker.finish(&mut decoded));
+    assert_eq!(Some(7), failed.unwrap_err().offset);
+
+    // A prefix cut off partway through a group decodes up to the cut
+    let codec = Base64Codec {
+        variant: Base64Variant::Standard,
+    };
+    assert_eq!(
+        b"aGVsbG8g",
+        aligned(b"aGVsbG8gd2", &codec.framing().unwrap())
+    );
+}
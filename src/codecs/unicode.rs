@@ -0,0 +1,302 @@
+use super::codec::Codec;
+use super::error::Error;
+use super::score;
+use crate::Format;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum UnicodeVariant {
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+impl UnicodeVariant {
+    pub fn all() -> Vec<Self> {
+        vec![Self::Utf16Le, Self::Utf16Be, Self::Utf32Le, Self::Utf32Be]
+    }
+}
+
+// Text in UTF-16 or UTF-32, decoded to utf8 and encoded from it
+pub struct UnicodeCodec {
+    pub variant: UnicodeVariant,
+    // Whether encoding starts with a byte order mark. Decoding always
+    // accepts one.
+    pub bom: bool,
+}
+
+impl Codec for UnicodeCodec {
+    fn format(&self) -> Format {
+        match self.variant {
+            UnicodeVariant::Utf16Le => Format::Utf16Le,
+            UnicodeVariant::Utf16Be => Format::Utf16Be,
+            UnicodeVariant::Utf32Le => Format::Utf32Le,
+            UnicodeVariant::Utf32Be => Format::Utf32Be,
+        }
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let width = self.width();
+        let start = self.body(&s)?;
+        if !(s.len() - start).is_multiple_of(width) {
+            return Err(Error::at(
+                format!("{} needs a multiple of {} bytes", self.format(), width),
+                s.len() - (s.len() - start) % width,
+            ));
+        }
+        let units = s[start..].chunks(width).map(|unit| self.unit(unit));
+        let mut out = String::new();
+        match width {
+            2 => {
+                let mut units = units.enumerate().peekable();
+                while let Some((i, unit)) = units.next() {
+                    let offset = start + i * width;
+                    let c = match unit {
+                        0xd800..=0xdbff => match units.peek() {
+                            Some(&(_, low @ 0xdc00..=0xdfff)) => {
+                                units.next();
+                                0x10000 + ((unit - 0xd800) << 10) + (low - 0xdc00)
+                            }
+                            _ => return Err(Self::unpaired(unit, offset)),
+                        },
+                        0xdc00..=0xdfff => return Err(Self::unpaired(unit, offset)),
+                        _ => unit,
+                    };
+                    // Safe since surrogates were paired up above
+                    out.push(char::from_u32(c).unwrap());
+                }
+            }
+            _ => {
+                for (i, unit) in units.enumerate() {
+                    let offset = start + i * width;
+                    match char::from_u32(unit) {
+                        Some(c) => out.push(c),
+                        None if (0xd800..=0xdfff).contains(&unit) => {
+                            return Err(Self::unpaired(unit, offset))
+                        }
+                        None => {
+                            return Err(Error::at(format!("Invalid code point {:x}", unit), offset))
+                        }
+                    }
+                }
+            }
+        }
+        Ok(out.into_bytes())
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let s = String::from_utf8(data)
+            .map_err(|e| Error::at("invalid utf8".to_string(), e.utf8_error().valid_up_to()))?;
+        let units: Vec<u32> = match self.width() {
+            2 => s.encode_utf16().map(u32::from).collect(),
+            _ => s.chars().map(u32::from).collect(),
+        };
+        let bom = if self.bom { vec![0xfeff] } else { vec![] };
+        Ok(bom
+            .into_iter()
+            .chain(units)
+            .flat_map(|unit| self.bytes(unit))
+            .collect())
+    }
+
+    fn printable(&self) -> bool {
+        false
+    }
+
+    fn confidence(&self, s: &[u8], decoded: &[u8]) -> f64 {
+        // A utf32le byte order mark, far more likely than a utf16le one
+        // followed by a NUL
+        if self.utf32le_mark(s) {
+            return 0.0;
+        }
+        if s.starts_with(&self.bytes(0xfeff)) {
+            return score::weigh(0.9, decoded);
+        }
+        // Most text people write is in the first 256 code points, so the
+        // high bytes of each unit are nearly all zero. Without that, any
+        // even number of bytes decodes to something.
+        let width = self.width();
+        let units = s.len() / width;
+        let zeros = s
+            .chunks(width)
+            .filter(|unit| self.unit(unit) < 0x100)
+            .count();
+        if units == 0 || zeros * 2 < units {
+            return 0.0;
+        }
+        score::weigh(zeros as f64 / units as f64, decoded)
+    }
+}
+
+impl UnicodeCodec {
+    fn width(&self) -> usize {
+        match self.variant {
+            UnicodeVariant::Utf16Le | UnicodeVariant::Utf16Be => 2,
+            UnicodeVariant::Utf32Le | UnicodeVariant::Utf32Be => 4,
+        }
+    }
+
+    fn little_endian(&self) -> bool {
+        matches!(
+            self.variant,
+            UnicodeVariant::Utf16Le | UnicodeVariant::Utf32Le
+        )
+    }
+
+    fn unit(&self, bytes: &[u8]) -> u32 {
+        let fold = |unit, &b| (unit << 8) | b as u32;
+        if self.little_endian() {
+            bytes.iter().rev().fold(0, fold)
+        } else {
+            bytes.iter().fold(0, fold)
+        }
+    }
+
+    fn bytes(&self, unit: u32) -> Vec<u8> {
+        let width = self.width();
+        let be = unit.to_be_bytes()[4 - width..].to_vec();
+        if self.little_endian() {
+            be.into_iter().rev().collect()
+        } else {
+            be
+        }
+    }
+
+    // Where the text starts, after the byte order mark if there is one. A
+    // mark for the other byte order means the wrong format was picked.
+    fn body(&self, s: &[u8]) -> Result<usize, Error> {
+        let width = self.width();
+        if s.len() < width {
+            return Ok(0);
+        }
+        match self.unit(&s[..width]) {
+            0xfeff => Ok(width),
+            0xfffe0000 | 0xfffe => Err(Error::at(
+                format!(
+                    "Byte order mark is {}, not {}",
+                    if self.little_endian() {
+                        "big-endian"
+                    } else {
+                        "little-endian"
+                    },
+                    if self.little_endian() {
+                        "little-endian"
+                    } else {
+                        "big-endian"
+                    }
+                ),
+                0,
+            )),
+            _ => Ok(0),
+        }
+    }
+
+    fn utf32le_mark(&self, s: &[u8]) -> bool {
+        self.width() == 2 && self.little_endian() && s.starts_with(&[0xff, 0xfe, 0, 0])
+    }
+
+    fn unpaired(unit: u32, offset: usize) -> Error {
+        Error::at(format!("Unpaired surrogate {:04x}", unit), offset)
+    }
+}
+
+#[test]
+fn decode() {
+    let tests = vec![
+        (UnicodeVariant::Utf16Le, vec![0x68, 0, 0x69, 0], "hi"),
+        (UnicodeVariant::Utf16Be, vec![0, 0x68, 0, 0x69], "hi"),
+        (
+            UnicodeVariant::Utf16Le,
+            vec![0xff, 0xfe, 0x68, 0, 0x69, 0],
+            "hi",
+        ),
+        (UnicodeVariant::Utf16Be, vec![0xd8, 0x3d, 0xde, 0x00], "😀"),
+        (
+            UnicodeVariant::Utf32Le,
+            vec![0x68, 0, 0, 0, 0, 0xf6, 0x01, 0],
+            "h😀",
+        ),
+        (
+            UnicodeVariant::Utf32Be,
+            vec![0, 0, 0xfe, 0xff, 0, 0, 0, 0xe9],
+            "é",
+        ),
+    ];
+
+    for (variant, input, expected) in tests {
+        let codec = UnicodeCodec {
+            variant,
+            bom: false,
+        };
+        assert_eq!(expected.as_bytes().to_vec(), codec.decode(input).unwrap());
+    }
+
+    let errors = vec![
+        (
+            UnicodeVariant::Utf16Le,
+            vec![0x68, 0, 0x3d, 0xd8, 0x69, 0],
+            "Unpaired surrogate d83d at byte 2",
+        ),
+        (
+            UnicodeVariant::Utf16Be,
+            vec![0, 0x68, 0xde, 0x00],
+            "Unpaired surrogate de00 at byte 2",
+        ),
+        (
+            UnicodeVariant::Utf16Le,
+            vec![0x68, 0, 0x69],
+            "utf16le needs a multiple of 2 bytes at byte 2",
+        ),
+        (
+            UnicodeVariant::Utf16Le,
+            vec![0xfe, 0xff, 0, 0x68],
+            "Byte order mark is big-endian, not little-endian at byte 0",
+        ),
+        (
+            UnicodeVariant::Utf32Be,
+            vec![0, 0x11, 0, 0],
+            "Invalid code point 110000 at byte 0",
+        ),
+    ];
+
+    for (variant, input, expected) in errors {
+        let codec = UnicodeCodec {
+            variant,
+            bom: false,
+        };
+        assert_eq!(expected, codec.decode(input).unwrap_err().to_string());
+    }
+}
+
+#[test]
+fn encode() {
+    let codec = UnicodeCodec {
+        variant: UnicodeVariant::Utf16Le,
+        bom: true,
+    };
+    assert_eq!(
+        vec![0xff, 0xfe, 0x68, 0, 0x3d, 0xd8, 0x00, 0xde],
+        codec.encode("h😀".as_bytes().to_vec()).unwrap()
+    );
+    let codec = UnicodeCodec {
+        variant: UnicodeVariant::Utf32Be,
+        bom: false,
+    };
+    assert_eq!(
+        vec![0, 0, 0, 0x68, 0, 0x01, 0xf6, 0],
+        codec.encode("h😀".as_bytes().to_vec()).unwrap()
+    );
+}
+
+#[test]
+fn utf32le_mark() {
+    // Named as utf16le it decodes, but it's never inferred as that
+    let input = vec![0xff, 0xfe, 0, 0, 0x68, 0, 0, 0];
+    let codec = UnicodeCodec {
+        variant: UnicodeVariant::Utf16Le,
+        bom: false,
+    };
+    let decoded = codec.decode(input.clone()).unwrap();
+    assert_eq!(b"\0h\0".to_vec(), decoded);
+    assert_eq!(0.0, codec.confidence(&input, &decoded));
+}
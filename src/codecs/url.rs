@@ -0,0 +1,198 @@
+use super::codec::Codec;
+use super::error::Error;
+use crate::Format;
+
+// How many layers of percent-encoding a recursive decode will peel
+const MAX_DEPTH: usize = 8;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum UrlMode {
+    // Leaves all RFC 3986 reserved characters alone, like encodeURI
+    Uri,
+    // A single path segment, so / is escaped
+    PathSegment,
+    // A single query key or value, so & = + and # are escaped
+    Query,
+    // application/x-www-form-urlencoded, where space is +
+    Form,
+}
+
+pub struct UrlCodec {
+    pub mode: UrlMode,
+    // Keep decoding until no escapes are left, for double-encoded input
+    pub recursive: bool,
+}
+
+impl Codec for UrlCodec {
+    fn format(&self) -> Format {
+        match (self.mode, self.recursive) {
+            (_, true) => Format::UrlRecursive,
+            (UrlMode::Uri, false) => Format::Url,
+            (UrlMode::PathSegment, false) => Format::UrlPath,
+            (UrlMode::Query, false) => Format::UrlQuery,
+            (UrlMode::Form, false) => Format::UrlForm,
+        }
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let mut data = self.decode_once(&s)?;
+        if self.recursive {
+            for _ in 1..MAX_DEPTH {
+                match self.decode_once(&data) {
+                    Ok(next) if next != data => data = next,
+                    // Stop at the first layer that isn't itself valid
+                    _ => break,
+                }
+            }
+        }
+        Ok(data)
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        // There's only one layer to add, so recursion is for decoding only
+        if self.recursive {
+            return Err(Error::new(format!(
+                "{} only decodes; encode with url",
+                self.format()
+            )));
+        }
+        Ok(data
+            .into_iter()
+            .map(|b| match b {
+                b' ' if self.mode == UrlMode::Form => "+".to_string(),
+                b if self.is_allowed(b) => (b as char).to_string(),
+                b => format!("%{:02X}", b),
+            })
+            .collect::<String>()
+            .into_bytes())
+    }
+
+    fn inferrable(&self) -> bool {
+        // Any text without a % would decode to itself
+        false
+    }
+}
+
+impl UrlCodec {
+    fn decode_once(&self, s: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::with_capacity(s.len());
+        let mut i = 0;
+        while i < s.len() {
+            match s[i] {
+                b'%' => {
+                    let byte = s
+                        .get(i + 1..i + 3)
+                        .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                        .and_then(|hex| std::str::from_utf8(hex).ok())
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                        .ok_or_else(|| Error::at("Malformed percent escape".to_string(), i))?;
+                    out.push(byte);
+                    i += 3;
+                }
+                b'+' if self.mode == UrlMode::Form => {
+                    out.push(b' ');
+                    i += 1;
+                }
+                b => {
+                    out.push(b);
+                    i += 1;
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    // Whether a byte can be written without escaping in this mode
+    fn is_allowed(&self, b: u8) -> bool {
+        let unreserved = b.is_ascii_alphanumeric() || b"-._~".contains(&b);
+        match self.mode {
+            UrlMode::Uri => unreserved || b":/?#[]@!$&'()*+,;=".contains(&b),
+            UrlMode::PathSegment => unreserved || b"!$&'()*+,;=:@".contains(&b),
+            UrlMode::Query => unreserved || b"!$'()*,;:@/?".contains(&b),
+            UrlMode::Form => b.is_ascii_alphanumeric() || b"*-._".contains(&b),
+        }
+    }
+}
+
+#[test]
+fn encode() {
+    use std::collections::HashMap;
+
+    let tests: HashMap<(&str, UrlMode), Vec<u8>> = [
+        (
+            ("https://rc.dev/a%20b?q=1&r=%C3%A9#top", UrlMode::Uri),
+            "https://rc.dev/a b?q=1&r=é#top".as_bytes().to_vec(),
+        ),
+        (
+            ("a%2Fb%20c;d=e", UrlMode::PathSegment),
+            "a/b c;d=e".as_bytes().to_vec(),
+        ),
+        (
+            ("a%26b%3Dc%2Bd/e?f", UrlMode::Query),
+            "a&b=c+d/e?f".as_bytes().to_vec(),
+        ),
+        (
+            ("a+b%2Bc%7E*", UrlMode::Form),
+            "a b+c~*".as_bytes().to_vec(),
+        ),
+        (("%00%FF", UrlMode::Uri), vec![0x00, 0xff]),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    for ((expected, mode), bytes) in tests {
+        let codec = UrlCodec {
+            mode,
+            recursive: false,
+        };
+        assert_eq!(expected.as_bytes().to_vec(), codec.encode(bytes).unwrap());
+    }
+}
+
+#[test]
+fn decode() {
+    use std::collections::HashMap;
+
+    let tests: HashMap<(&str, UrlMode, bool), Result<Vec<u8>, Error>> = [
+        (
+            ("a%20b+c%2b", UrlMode::Uri, false),
+            Ok("a b+c+".as_bytes().to_vec()),
+        ),
+        (
+            ("a%20b+c%2b", UrlMode::Form, false),
+            Ok("a b c+".as_bytes().to_vec()),
+        ),
+        (
+            ("%252541", UrlMode::Uri, false),
+            Ok("%2541".as_bytes().to_vec()),
+        ),
+        (("%252541", UrlMode::Uri, true), Ok("A".as_bytes().to_vec())),
+        (
+            ("100%2525", UrlMode::Uri, true),
+            Ok("100%".as_bytes().to_vec()),
+        ),
+        (
+            ("ab%2", UrlMode::Uri, false),
+            Err(Error::at("Malformed percent escape".to_string(), 2)),
+        ),
+        (
+            ("a%zzb", UrlMode::Form, false),
+            Err(Error::at("Malformed percent escape".to_string(), 1)),
+        ),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+
+    for ((data, mode, recursive), expected) in tests {
+        let codec = UrlCodec { mode, recursive };
+        assert_eq!(expected, codec.decode(data.as_bytes().to_vec()));
+    }
+
+    let recursive = UrlCodec {
+        mode: UrlMode::Uri,
+        recursive: true,
+    };
+    assert!(recursive.encode(b"a b".to_vec()).is_err());
+}
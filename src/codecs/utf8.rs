@@ -1,5 +1,6 @@
 use super::codec::Codec;
 use super::error::Error;
+use super::score;
 use crate::Format;
 
 pub struct Utf8Codec {}
@@ -16,7 +17,14 @@ impl Codec for Utf8Codec {
         }
     }
 
-    fn encode(&self, data: Vec<u8>) -> Result<String, Error> {
-        String::from_utf8(data).map_err(|_| Error::new("invalid utf8".to_string()))
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        String::from_utf8(data)
+            .map(String::into_bytes)
+            .map_err(|_| Error::new("invalid utf8".to_string()))
+    }
+
+    fn confidence(&self, _s: &[u8], decoded: &[u8]) -> f64 {
+        // Any text is utf8, so fitting it says nothing
+        score::weigh(0.0, decoded)
     }
 }
@@ -0,0 +1,95 @@
+use super::codec::{Codec, Framing};
+use super::error::Error;
+use crate::Format;
+
+// XORs data with a key, repeated as often as it takes to cover the data.
+// Doing it twice gives the data back, so decoding is the same as encoding.
+pub struct XorCodec {
+    // None until a key is given, so the format can be listed but not used
+    pub key: Option<Vec<u8>>,
+}
+
+impl Codec for XorCodec {
+    fn format(&self) -> Format {
+        Format::Xor
+    }
+
+    fn decode(&self, s: Vec<u8>) -> Result<Vec<u8>, Error> {
+        self.apply(s)
+    }
+
+    fn encode(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        self.apply(data)
+    }
+
+    fn inferrable(&self) -> bool {
+        false
+    }
+
+    fn printable(&self) -> bool {
+        false
+    }
+
+    // Whole repeats of the key line up on their own
+    fn framing(&self) -> Option<Framing<'_>> {
+        self.key
+            .as_ref()
+            .filter(|key| !key.is_empty())
+            .map(|key| Framing {
+                symbols: key.len(),
+                bytes: key.len(),
+                ignored: |_| false,
+                padding: None,
+                separator: "",
+            })
+    }
+}
+
+impl XorCodec {
+    fn apply(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        match &self.key {
+            Some(key) if !key.is_empty() => Ok(xor(&data, key)),
+            Some(_) => Err(Error::new(format!("{} key is empty", self.format()))),
+            None => Err(Error::new(format!(
+                "No key given for {}; use --key",
+                self.format()
+            ))),
+        }
+    }
+}
+
+pub fn xor(data: &[u8], key: &[u8]) -> Vec<u8> {
+    data.iter()
+        .zip(key.iter().cycle())
+        .map(|(d, k)| d ^ k)
+        .collect()
+}
+
+#[test]
+fn encode() {
+    let tests = vec![
+        (
+            vec![0x2a],
+            b"hello".to_vec(),
+            vec![0x42, 0x4f, 0x46, 0x46, 0x45],
+        ),
+        (
+            b"ICE".to_vec(),
+            b"Burning 'em".to_vec(),
+            vec![
+                0x0b, 0x36, 0x37, 0x27, 0x2a, 0x2b, 0x2e, 0x63, 0x62, 0x2c, 0x2e,
+            ],
+        ),
+    ];
+
+    for (key, plain, encoded) in tests {
+        let codec = XorCodec { key: Some(key) };
+        assert_eq!(encoded, codec.encode(plain.clone()).unwrap());
+        assert_eq!(plain, codec.decode(encoded).unwrap());
+    }
+
+    assert!(XorCodec { key: None }.encode(b"abc".to_vec()).is_err());
+    assert!(XorCodec { key: Some(vec![]) }
+        .encode(b"abc".to_vec())
+        .is_err());
+}
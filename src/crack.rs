@@ -0,0 +1,338 @@
+use crate::codecs::cipher::{Cipher, CipherCodec};
+use crate::codecs::codec::Codec;
+use crate::codecs::error::Error;
+use crate::codecs::score::{english, english_text, letter_counts};
+use crate::codecs::xor;
+use crate::options::Format;
+use std::collections::HashSet;
+
+// Longest Vigenère key to look for
+const MAX_KEY_LENGTH: usize = 20;
+
+// How many of the likeliest key lengths get a key worked out for them
+const KEY_LENGTHS_TRIED: usize = 3;
+
+// Key lengths whose columns are within this fraction of the most English
+// index of coincidence are all worth trying. Multiples of the real length
+// score about as well as it does, or better on short texts.
+const KEY_LENGTH_SLACK: f64 = 0.9;
+
+// A little under English's index of coincidence. Any key length whose
+// columns reach it is worth trying too.
+const ENGLISH_COINCIDENCE: f64 = 0.06;
+
+// Longest repeating XOR key to look for
+const MAX_XOR_KEY_LENGTH: usize = 40;
+
+// How many of the likeliest XOR key lengths get a key worked out for them
+const XOR_KEY_LENGTHS_TRIED: usize = 5;
+
+// How many blocks to compare when guessing an XOR key's length
+const HAMMING_BLOCKS: usize = 32;
+
+// Longer XOR keys always fit the data a little better, so a shorter key
+// whose result scores within this factor of the best is taken instead
+const XOR_SCORE_SLACK: f64 = 1.5;
+
+// What was undone to get a solution
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Method {
+    Cipher(Cipher),
+    Xor(Vec<u8>),
+}
+
+// A way of deciphering the input, and how much the result looks like English
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub method: Method,
+    pub data: Vec<u8>,
+    // Chi-squared distance from English letter frequencies; lower is better
+    pub score: f64,
+}
+
+// The format a --crack target undoes
+pub fn format(target: &str) -> Option<Format> {
+    match target {
+        "caesar" => Some(Format::Caesar),
+        "vigenere" => Some(Format::Vigenere),
+        "xor" => Some(Format::Xor),
+        _ => None,
+    }
+}
+
+// Tries all 26 shifts, most English first
+pub fn caesar(data: &[u8]) -> Result<Vec<Solution>, Error> {
+    let mut found = solve(data, (0..26).map(Cipher::Caesar).collect())?;
+    // The sort is stable, so ties keep the smaller shift first
+    found.sort_by(|a, b| a.score.total_cmp(&b.score));
+    Ok(found)
+}
+
+// Guesses the key length from the index of coincidence of the letters that
+// would share a shift, then solves each shift like a caesar cipher. The
+// likeliest few lengths are tried, shortest first: longer keys always fit
+// letter frequencies at least as well, so the score can't choose between them.
+pub fn vigenere(data: &[u8]) -> Result<Vec<Solution>, Error> {
+    let letters = data
+        .iter()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase() - b'a')
+        .collect::<Vec<_>>();
+    let lengths = (1..=MAX_KEY_LENGTH.min(letters.len() / 2).max(1))
+        .map(|length| {
+            let columns = (0..length)
+                .map(|start| {
+                    letters
+                        .iter()
+                        .skip(start)
+                        .step_by(length)
+                        .cloned()
+                        .collect()
+                })
+                .collect::<Vec<Vec<u8>>>();
+            let ioc = columns.iter().map(|c| coincidence(c)).sum::<f64>() / length as f64;
+            (length, columns, ioc)
+        })
+        .collect::<Vec<_>>();
+    let best = lengths.iter().map(|(_, _, ioc)| *ioc).fold(0.0, f64::max);
+    let threshold = (best * KEY_LENGTH_SLACK).min(ENGLISH_COINCIDENCE);
+
+    let ciphers = lengths
+        .into_iter()
+        .filter(|(_, _, ioc)| *ioc >= threshold)
+        .take(KEY_LENGTHS_TRIED)
+        .map(|(_, columns, _)| {
+            let key = columns
+                .iter()
+                .map(|column| {
+                    (0..26u8)
+                        .min_by(|&a, &b| {
+                            english(&shifted(column, a)).total_cmp(&english(&shifted(column, b)))
+                        })
+                        .map(|shift| shift + b'A')
+                        .unwrap()
+                })
+                .collect();
+            Cipher::Vigenere(Some(key))
+        })
+        .collect();
+    solve(data, ciphers)
+}
+
+// Deciphers data every way given. Ciphers that come to the same thing are
+// only listed once, keeping whichever was given first.
+fn solve(data: &[u8], ciphers: Vec<Cipher>) -> Result<Vec<Solution>, Error> {
+    let found = ciphers
+        .into_iter()
+        .map(|cipher| {
+            let data = CipherCodec {
+                cipher: cipher.clone(),
+            }
+            .decode(data.to_vec())?;
+            Ok(Solution {
+                score: english(&data),
+                method: Method::Cipher(cipher),
+                data,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(distinct(found))
+}
+
+// Guesses repeating key lengths from how few bits differ between blocks of
+// each length, since blocks XORed with the same key differ only as much as
+// the text does. Each byte of the key is then found like a single-byte key.
+// Keys for the likeliest lengths come first, then every single-byte key from
+// most to least English.
+pub fn xor(data: &[u8]) -> Vec<Solution> {
+    let mut lengths = (1..=MAX_XOR_KEY_LENGTH.min(data.len() / 2))
+        .map(|length| (length, hamming_distance(data, length)))
+        .collect::<Vec<_>>();
+    lengths.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let mut found = lengths
+        .into_iter()
+        .take(XOR_KEY_LENGTHS_TRIED)
+        .map(|(length, _)| {
+            let key = (0..length)
+                .map(|start| {
+                    let column = data
+                        .iter()
+                        .skip(start)
+                        .step_by(length)
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    single_byte(&column)[0]
+                })
+                .collect::<Vec<_>>();
+            let key = period(key);
+            (key.len(), xor_solution(data, key))
+        })
+        .collect::<Vec<_>>();
+    found.sort_by(|a, b| a.1.score.total_cmp(&b.1.score));
+    let best = found.first().map_or(f64::INFINITY, |(_, s)| s.score);
+    let close = found
+        .iter()
+        .take_while(|(_, s)| s.score <= best * XOR_SCORE_SLACK)
+        .count();
+    found[..close].sort_by_key(|(length, _)| *length);
+
+    let mut found = found.into_iter().map(|(_, s)| s).collect::<Vec<_>>();
+    found.extend(
+        single_byte(data)
+            .into_iter()
+            .map(|k| xor_solution(data, vec![k])),
+    );
+    distinct(found)
+}
+
+fn xor_solution(data: &[u8], key: Vec<u8>) -> Solution {
+    let data = xor::xor(data, &key);
+    Solution {
+        score: english_text(&data),
+        method: Method::Xor(key),
+        data,
+    }
+}
+
+// The shortest key that the key mostly repeats, or the key itself. A
+// multiple of the real length always fits the data a little better, so
+// each byte of the shorter key only has to win a majority of its repeats.
+fn period(key: Vec<u8>) -> Vec<u8> {
+    (1..key.len())
+        .filter(|p| key.len().is_multiple_of(*p))
+        .find_map(|p| {
+            (0..p)
+                .map(|start| {
+                    let mut counts = [0; 256];
+                    key.iter()
+                        .skip(start)
+                        .step_by(p)
+                        .for_each(|&b| counts[b as usize] += 1);
+                    let repeats = key.len() / p;
+                    (0..=255u8).find(|&b| counts[b as usize] * 2 > repeats)
+                })
+                .collect::<Option<Vec<_>>>()
+        })
+        .map(period)
+        .unwrap_or(key)
+}
+
+// Every byte, from the one that leaves data most like English to the least
+fn single_byte(data: &[u8]) -> Vec<u8> {
+    let mut keys = (0..=255u8)
+        .map(|k| (k, english_text(&xor::xor(data, &[k]))))
+        .collect::<Vec<_>>();
+    keys.sort_by(|a, b| a.1.total_cmp(&b.1));
+    keys.into_iter().map(|(k, _)| k).collect()
+}
+
+// Bits that differ between blocks of this length, per byte, averaged over
+// every pair of the first few blocks
+fn hamming_distance(data: &[u8], length: usize) -> f64 {
+    let blocks = data
+        .chunks_exact(length)
+        .take(HAMMING_BLOCKS)
+        .collect::<Vec<_>>();
+    let mut total = 0.0;
+    let mut pairs = 0;
+    for (i, a) in blocks.iter().enumerate() {
+        for b in &blocks[i + 1..] {
+            let bits = a.iter().zip(*b).map(|(x, y)| (x ^ y).count_ones());
+            total += bits.sum::<u32>() as f64 / length as f64;
+            pairs += 1;
+        }
+    }
+    total / pairs as f64
+}
+
+// Drops solutions that come to the same thing as one before them
+fn distinct(mut found: Vec<Solution>) -> Vec<Solution> {
+    let mut seen = HashSet::new();
+    found.retain(|s| seen.insert(s.data.clone()));
+    found
+}
+
+// Letters from 0 to 25, shifted back as if deciphering a caesar cipher
+fn shifted(letters: &[u8], shift: u8) -> Vec<u8> {
+    letters
+        .iter()
+        .map(|x| (x + 26 - shift) % 26 + b'a')
+        .collect()
+}
+
+// The chance that two letters picked from these are the same. English is
+// around 0.067, and evenly spread letters are 1/26.
+fn coincidence(letters: &[u8]) -> f64 {
+    let n = letters.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let counts = letter_counts(&shifted(letters, 0));
+    let same = counts
+        .iter()
+        .map(|c| c * c.saturating_sub(1))
+        .sum::<usize>();
+    same as f64 / (n * (n - 1)) as f64
+}
+
+impl Solution {
+    // The arguments that would encipher the data back to the input
+    pub fn describe(&self) -> String {
+        match &self.method {
+            Method::Cipher(Cipher::Caesar(shift)) => format!("caesar --shift {}", shift),
+            Method::Cipher(Cipher::Vigenere(Some(key))) => {
+                format!("vigenere --key {}", String::from_utf8_lossy(key))
+            }
+            Method::Cipher(cipher) => format!("{:?}", cipher),
+            Method::Xor(key) => format!(
+                "xor --key hex:{}",
+                key.iter().map(|b| format!("{:02x}", b)).collect::<String>()
+            ),
+        }
+    }
+}
+
+#[test]
+fn crack() {
+    let plain = "It was the best of times, it was the worst of times, it was the age \
+                 of wisdom, it was the age of foolishness, it was the epoch of belief, \
+                 it was the epoch of incredulity, it was the season of Light, it was \
+                 the season of Darkness.";
+
+    let shifted = CipherCodec {
+        cipher: Cipher::Caesar(7),
+    }
+    .encode(plain.as_bytes().to_vec())
+    .unwrap();
+    let best = &caesar(&shifted).unwrap()[0];
+    assert_eq!(Method::Cipher(Cipher::Caesar(7)), best.method);
+    assert_eq!(plain.as_bytes().to_vec(), best.data);
+    assert_eq!(26, caesar(&shifted).unwrap().len());
+
+    let keyed = CipherCodec {
+        cipher: Cipher::Vigenere(Some(b"DICKENS".to_vec())),
+    }
+    .encode(plain.as_bytes().to_vec())
+    .unwrap();
+    let best = &vigenere(&keyed).unwrap()[0];
+    assert_eq!(
+        Method::Cipher(Cipher::Vigenere(Some(b"DICKENS".to_vec()))),
+        best.method
+    );
+    assert_eq!(plain.as_bytes().to_vec(), best.data);
+
+    assert!(caesar(&[0xff, 0xfe]).is_err());
+
+    let single = xor::xor(plain.as_bytes(), &[0x5a]);
+    let best = &xor(&single)[0];
+    assert_eq!(Method::Xor(vec![0x5a]), best.method);
+    assert_eq!(plain.as_bytes().to_vec(), best.data);
+
+    for key in [&b"rc!"[..], b"hunter2", b"SECRETKEY"] {
+        let repeating = xor::xor(plain.as_bytes(), key);
+        let best = &xor(&repeating)[0];
+        assert_eq!(Method::Xor(key.to_vec()), best.method);
+        assert_eq!(plain.as_bytes().to_vec(), best.data);
+    }
+}
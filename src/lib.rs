@@ -0,0 +1,23 @@
+extern crate blake2;
+extern crate blake3;
+extern crate encoding_rs;
+extern crate flate2;
+extern crate hmac;
+extern crate md5;
+extern crate sha1;
+extern crate sha2;
+extern crate sha3;
+extern crate subtle;
+extern crate unicode_segmentation;
+
+pub mod codecs;
+pub mod crack;
+pub mod magic;
+pub mod options;
+pub mod pipeline;
+pub mod registry;
+pub mod transform;
+
+pub use options::Format;
+pub use pipeline::Pipeline;
+pub use registry::Registry;
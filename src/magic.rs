@@ -0,0 +1,104 @@
+use crate::codecs::score::readability;
+use crate::pipeline::{Pipeline, Stage};
+use crate::registry::Registry;
+use std::collections::HashSet;
+
+// Stop searching once this many distinct decodings have been found
+const MAX_CANDIDATES: usize = 1000;
+
+// One way of decoding the input, and how readable the result is
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub recipe: Pipeline,
+    pub data: Vec<u8>,
+    pub score: f64,
+}
+
+// Tries every inferrable codec on the input, then on each of the results,
+// up to depth layers deep. Candidates are returned most readable first,
+// preferring longer recipes when they read equally well.
+pub fn search(registry: &Registry, value: Vec<u8>, depth: usize) -> Vec<Candidate> {
+    let codecs = registry
+        .codecs()
+        .iter()
+        .filter(|codec| codec.inferrable())
+        .collect::<Vec<_>>();
+
+    let mut seen = HashSet::new();
+    seen.insert(value.clone());
+    let mut frontier = vec![Candidate {
+        recipe: Pipeline { stages: vec![] },
+        score: readability(&value),
+        data: value,
+    }];
+    let mut found = frontier.clone();
+
+    for _ in 0..depth {
+        let mut next = vec![];
+        for candidate in &frontier {
+            for codec in &codecs {
+                if seen.len() >= MAX_CANDIDATES {
+                    break;
+                }
+                match codec.decode(candidate.data.clone()) {
+                    // Skip decodings that go nowhere, like utf8
+                    Ok(data) if !data.is_empty() && seen.insert(data.clone()) => {
+                        let mut recipe = candidate.recipe.clone();
+                        recipe.stages.push(Stage::From(codec.format()));
+                        next.push(Candidate {
+                            recipe,
+                            score: readability(&data),
+                            data,
+                        });
+                    }
+                    _ => (),
+                }
+            }
+        }
+        found.extend(next.iter().cloned());
+        frontier = next;
+    }
+
+    found.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.recipe.stages.len().cmp(&a.recipe.stages.len()))
+    });
+    found
+}
+
+impl Candidate {
+    pub fn describe(&self) -> String {
+        if self.recipe.stages.is_empty() {
+            "(input)".to_string()
+        } else {
+            self.recipe.to_string()
+        }
+    }
+}
+
+#[test]
+fn search_layers() {
+    use crate::options::Format;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let registry = Registry::new();
+    // base 64 of hex of gzip
+    let mut gz = GzEncoder::new(vec![], Compression::default());
+    gz.write_all(b"hello magic").unwrap();
+    let hex = registry.encode(Format::Hex, gz.finish().unwrap()).unwrap();
+    let b64 = registry.encode(Format::Base64, hex).unwrap();
+
+    let best = &search(&registry, b64, 4)[0];
+    assert_eq!(b"hello magic".to_vec(), best.data);
+    assert_eq!(
+        vec![
+            Stage::From(Format::Base64),
+            Stage::From(Format::Hex),
+            Stage::From(Format::Gzip)
+        ],
+        best.recipe.stages
+    );
+}
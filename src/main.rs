@@ -1,18 +1,33 @@
 extern crate clap;
 extern crate isatty;
+extern crate rc;
 
-mod codecs;
-mod options;
+mod render;
 
 use clap::{App, Arg};
-use codecs::codec::Codec;
-use codecs::error::Error;
 use isatty::stdout_isatty;
-use options::Format;
+use rc::codecs::checksum;
+use rc::codecs::cipher::{Cipher, CipherCodec};
+use rc::codecs::cjk::{CjkCodec, CjkEncoding};
+use rc::codecs::digest::{Algorithm, DigestCodec, DigestText};
+use rc::codecs::error::Error;
+use rc::codecs::hex::{HexCodec, HexStyle};
+use rc::codecs::hexdump::{DumpLayout, HexdumpCodec};
+use rc::codecs::mac;
+use rc::codecs::mac::{HmacCodec, MacAlgorithm};
+use rc::codecs::unicode::{UnicodeCodec, UnicodeVariant};
+use rc::codecs::xor::XorCodec;
+use rc::crack;
+use rc::magic;
+use rc::options::{Format, Style};
+use rc::transform::Transform;
+use rc::{Pipeline, Registry};
+use render::{Output, Report};
 use std::ffi::OsString;
 use std::io;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::process;
+use std::str::FromStr;
 
 // Windows uses UTF-16. We'll convert it to UTF-8 and give the bytes.
 #[cfg(target_os = "windows")]
@@ -33,6 +48,15 @@ fn u8_arg(s: &OsString) -> Result<Vec<u8>, Error> {
     Ok(s.to_owned().into_vec())
 }
 
+// How much of a stream to look at when inferring its format
+const PREFIX_LENGTH: usize = 64 * 1024;
+
+// How many of the most readable --magic results to show
+const MAGIC_RESULTS: usize = 5;
+
+// How many of the most English --crack results to show
+const CRACK_RESULTS: usize = 5;
+
 fn main() {
     let matches = App::new("rc")
         .version("1.0")
@@ -60,6 +84,58 @@ fn main() {
                 .multiple(true)
                 .possible_values(&Format::all_variants()),
         )
+        .arg(
+            Arg::new("pipeline")
+                .about("Stages to run in order, like 'from:base64 | from:hex | rot13 | to:utf8'")
+                .takes_value(true)
+                .value_name("stages")
+                .short('p')
+                .long("pipeline")
+                .conflicts_with_all(&["from", "to"]),
+        )
+        .arg(
+            Arg::new("magic")
+                .about("Search for layered encodings and show the most readable results")
+                .short('m')
+                .long("magic")
+                .conflicts_with_all(&["from", "to", "pipeline"]),
+        )
+        .arg(
+            Arg::new("depth")
+                .about("How many layers of encoding --magic will peel")
+                .takes_value(true)
+                .value_name("layers")
+                .long("depth")
+                .default_value("4"),
+        )
+        .arg(
+            Arg::new("crc-match")
+                .about("Report which catalogued CRCs of the input come to this hex value")
+                .takes_value(true)
+                .value_name("checksum")
+                .long("crc-match")
+                .conflicts_with_all(&["to", "pipeline", "magic"]),
+        )
+        .arg(
+            Arg::new("crack")
+                .about("Try to break a cipher or XOR key and show the most English results")
+                .takes_value(true)
+                .value_name("cipher")
+                .long("crack")
+                .possible_values(&["caesar", "vigenere", "xor"])
+                .conflicts_with_all(&["to", "pipeline", "magic", "crc-match"]),
+        )
+        .arg(
+            Arg::new("transform")
+                .about("Rewrite the decoded text before encoding it, in the order given")
+                .takes_value(true)
+                .value_name("transform")
+                .long("transform")
+                .number_of_values(1)
+                .multiple(true)
+                .possible_values(&Transform::all_variants())
+                .conflicts_with_all(&["pipeline", "magic", "crc-match", "crack", "expect"]),
+        )
         .arg(
             Arg::new("as")
                 .about("How to display the output")
@@ -67,7 +143,93 @@ fn main() {
                 .value_name("format")
                 .short('a')
                 .long("as")
-                .default_value("text"),
+                .default_value("text")
+                .possible_values(&Style::all_variants()),
+        )
+        .arg(
+            Arg::new("digest-as")
+                .about("How to write hash digests")
+                .takes_value(true)
+                .value_name("format")
+                .long("digest-as")
+                .default_value("hex")
+                .possible_values(&["hex", "base64"]),
+        )
+        .arg(
+            Arg::new("width")
+                .about("Bytes per line of xxd and hexdump output")
+                .takes_value(true)
+                .value_name("n")
+                .long("width"),
+        )
+        .arg(
+            Arg::new("group")
+                .about("Bytes per group of xxd and hexdump output [default: 2 for xxd, 8 for hexdump]")
+                .takes_value(true)
+                .value_name("n")
+                .long("group"),
+        )
+        .arg(
+            Arg::new("hex-style")
+                .about("How to write hex: plain digits, 0x or \\x per byte, or an array literal")
+                .takes_value(true)
+                .value_name("style")
+                .long("hex-style")
+                .possible_values(&HexStyle::all_variants()),
+        )
+        .arg(
+            Arg::new("hex-upper")
+                .about("Write hex digits in uppercase")
+                .long("hex-upper"),
+        )
+        .arg(
+            Arg::new("hex-separator")
+                .about("What goes between bytes of hex [default: none for plain and \\x, \", \" otherwise]")
+                .takes_value(true)
+                .value_name("sep")
+                .long("hex-separator")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::new("key")
+                .about("Key for HMAC, keyed ciphers and XOR, in utf8 or as format:value, like hex:00ff")
+                .takes_value(true)
+                .value_name("key")
+                .long("key"),
+        )
+        .arg(
+            Arg::new("shift")
+                .about("How far the caesar cipher shifts letters")
+                .takes_value(true)
+                .value_name("n")
+                .long("shift")
+                .allow_hyphen_values(true)
+                .default_value("3"),
+        )
+        .arg(
+            Arg::new("affine")
+                .about("The a and b of the affine cipher's a * x + b, like 5,8")
+                .takes_value(true)
+                .value_name("a,b")
+                .long("affine"),
+        )
+        .arg(
+            Arg::new("bom")
+                .about("Start utf16 and utf32 output with a byte order mark")
+                .long("bom"),
+        )
+        .arg(
+            Arg::new("lossy")
+                .about("Decode invalid CJK sequences as U+FFFD instead of failing")
+                .long("lossy"),
+        )
+        .arg(
+            Arg::new("expect")
+                .about("Check the output against this value and exit 0 on a match, 1 otherwise")
+                .takes_value(true)
+                .value_name("value")
+                .long("expect")
+                .conflicts_with_all(&["pipeline", "magic", "crc-match", "crack"]),
         )
         .arg(
             Arg::new("verbose")
@@ -84,16 +246,169 @@ fn main() {
         )
         .get_matches();
 
-    let from = matches.value_of("from").unwrap_or_else(|| "__infer");
+    // --depth has a default, so clap can't require --magic alongside it
+    if matches.occurrences_of("depth") > 0 && !matches.is_present("magic") {
+        println!("--depth only applies to --magic");
+        process::exit(1)
+    }
+    let mut registry = Registry::new();
+    let text = match matches.value_of("digest-as") {
+        Some("base64") => DigestText::Base64,
+        _ => DigestText::Hex,
+    };
+    if text != DigestText::Hex {
+        Algorithm::all()
+            .into_iter()
+            .for_each(|algorithm| registry.register(Box::new(DigestCodec { algorithm, text })));
+    }
+    if matches.is_present("width") || matches.is_present("group") {
+        let count = |arg: &str| {
+            matches.value_of(arg).map(|n| {
+                n.parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .unwrap_or_else(|| {
+                        println!("Invalid {} {}", arg, n);
+                        process::exit(1)
+                    })
+            })
+        };
+        let width = count("width").unwrap_or(16);
+        let group = count("group");
+        registry.register(Box::new(HexdumpCodec {
+            layout: DumpLayout::Xxd,
+            width,
+            group: group.unwrap_or(2),
+        }));
+        registry.register(Box::new(HexdumpCodec {
+            layout: DumpLayout::Canonical,
+            width,
+            group: group.unwrap_or(8),
+        }));
+    }
+    if matches.is_present("hex-style")
+        || matches.is_present("hex-upper")
+        || matches.is_present("hex-separator")
+    {
+        let style = matches
+            .value_of("hex-style")
+            .map_or(HexStyle::Plain, |style| style.parse().unwrap());
+        let separator = matches
+            .value_of("hex-separator")
+            .unwrap_or_else(|| style.separator())
+            .to_string();
+        registry.register(Box::new(HexCodec {
+            style,
+            upper: matches.is_present("hex-upper"),
+            separator,
+        }));
+    }
+    if let Some(key) = matches.value_of("key") {
+        let key = registry.decode_spec(key, Format::Utf8).unwrap_or_else(|e| {
+            println!("Invalid key! {}", e);
+            process::exit(2)
+        });
+        MacAlgorithm::all().into_iter().for_each(|algorithm| {
+            registry.register(Box::new(HmacCodec {
+                algorithm,
+                key: Some(key.clone()),
+                text,
+            }))
+        });
+        vec![
+            Cipher::Vigenere(Some(key.clone())),
+            Cipher::Beaufort(Some(key.clone())),
+            Cipher::Substitution(Some(key.clone())),
+        ]
+        .into_iter()
+        .for_each(|cipher| registry.register(Box::new(CipherCodec { cipher })));
+        registry.register(Box::new(XorCodec { key: Some(key) }));
+    }
+    // Safe since the argument has a default value
+    let shift = matches.value_of("shift").unwrap();
+    let shift = shift.parse::<i64>().unwrap_or_else(|_| {
+        println!("Invalid shift {}", shift);
+        process::exit(1)
+    });
+    registry.register(Box::new(CipherCodec {
+        cipher: Cipher::Caesar(shift.rem_euclid(26) as u8),
+    }));
+    if let Some(affine) = matches.value_of("affine") {
+        let params = affine
+            .split_once(',')
+            .and_then(|(a, b)| Some((a.trim().parse::<i64>().ok()?, b.trim().parse::<i64>().ok()?)))
+            .unwrap_or_else(|| {
+                println!("Invalid affine parameters {}", affine);
+                process::exit(1)
+            });
+        registry.register(Box::new(CipherCodec {
+            cipher: Cipher::Affine(Some((
+                params.0.rem_euclid(26) as u8,
+                params.1.rem_euclid(26) as u8,
+            ))),
+        }));
+    }
+    if matches.is_present("bom") {
+        UnicodeVariant::all()
+            .into_iter()
+            .for_each(|variant| registry.register(Box::new(UnicodeCodec { variant, bom: true })));
+    }
+    if matches.is_present("lossy") {
+        CjkEncoding::all().into_iter().for_each(|encoding| {
+            registry.register(Box::new(CjkCodec {
+                encoding,
+                replace: true,
+            }))
+        });
+    }
+
     let to = {
         if let Some(v) = matches.values_of("to") {
-            v.collect::<Vec<_>>()
+            // A format asked for twice is only shown once, which JSON needs
+            // since its keys have to be unique
+            let mut to = Vec::<&str>::new();
+            for f in v {
+                let format = Format::from_str(f).unwrap();
+                if !to.iter().any(|t| Format::from_str(t).unwrap() == format) {
+                    to.push(f);
+                }
+            }
+            to
         } else {
             vec!["utf8", "hex", "base64"]
         }
     };
-    let _as = matches.value_of("as").unwrap_or_else(|| "text");
+    // Hashing a file means hashing its bytes, not whatever they might decode to
+    let one_way = to.iter().all(|f| {
+        registry
+            .get(Format::from_str(f).unwrap())
+            .is_some_and(|codec| !codec.decodable())
+    });
+    let from = match matches.value_of("from") {
+        Some(from) => from,
+        None if one_way || matches.is_present("crc-match") || matches.is_present("crack") => "raw",
+        None => "__infer",
+    };
+    let style = matches.value_of("as").unwrap_or("text");
     let verbosity = matches.occurrences_of("verbose");
+
+    // When the output is nothing but the converted data, it can be written
+    // as it's made, so input on stdin never has to fit in memory. Formats
+    // without framing (unicode, charsets, hexdump, escapes) still read it all
+    let streaming = !matches.is_present("value")
+        && !matches.is_present("magic")
+        && !matches.is_present("pipeline")
+        && !matches.is_present("crc-match")
+        && !matches.is_present("expect")
+        && !matches.is_present("crack")
+        && !matches.is_present("transform")
+        && to.len() == 1
+        && (style == "raw" || (style == "text" && !stdout_isatty()));
+    if streaming {
+        stream(&registry, from, to[0]);
+        return;
+    }
+
     let value = &{
         if let Some(v) = matches.values_of_os("value") {
             match v
@@ -104,7 +419,7 @@ fn main() {
                 .collect::<Result<Vec<Vec<u8>>, Error>>()
                 .map(|o| {
                     o.into_iter()
-                        .reduce(|sum, cur| vec![sum, vec![0x20], cur].concat())
+                        .reduce(|sum, cur| [sum, vec![0x20], cur].concat())
                         .unwrap()
                 }) {
                 Ok(fin) => fin,
@@ -123,136 +438,300 @@ fn main() {
         }
     };
 
-    decode_encode(from, to, _as, verbosity, value.to_vec());
+    if matches.is_present("magic") {
+        // Safe since the argument has a default value
+        let depth = matches.value_of("depth").unwrap();
+        let depth = depth.parse::<usize>().unwrap_or_else(|_| {
+            println!("Invalid depth {}", depth);
+            process::exit(1)
+        });
+        run_magic(&registry, depth, value.to_vec());
+    } else if let Some(cipher) = matches.value_of("crack") {
+        run_crack(&registry, cipher, from, value.to_vec());
+    } else if let Some(expected) = matches.value_of("expect") {
+        run_expect(&registry, expected, from, &to, text, value.to_vec());
+    } else if let Some(expected) = matches.value_of("crc-match") {
+        run_crc_match(&registry, expected, from, value.to_vec());
+    } else if let Some(stages) = matches.value_of("pipeline") {
+        run_pipeline(&registry, stages, verbosity, value.to_vec());
+    } else {
+        // Safe since the argument parser validates these values
+        let transforms = matches
+            .values_of("transform")
+            .map(|v| {
+                v.map(|t| Transform::from_str(t).unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        decode_encode(
+            &registry,
+            from,
+            &transforms,
+            to,
+            style,
+            verbosity,
+            value.to_vec(),
+        );
+    }
 }
 
-fn decode_encode(from: &str, to: Vec<&str>, _as: &str, verbosity: u64, value: Vec<u8>) {
+fn stream(registry: &Registry, from: &str, to: &str) {
     // These unwrap()s are safe since the argument parser validates these values exist
     let from_format = Format::from_str(from).unwrap();
-    // TODO dedupe output formats
-    let to_formats = to
-        .into_iter()
-        .map(|v| Format::from_str(v).unwrap())
-        .collect::<Vec<_>>();
+    let to_format = Format::from_str(to).unwrap();
 
-    match decode(from_format, value) {
-        (used_format, Ok(data)) => {
-            if stdout_isatty() && (from_format == Format::Inferred || verbosity > 0) {
-                println!(
-                    "\t[{}{}~> {}]\n",
-                    used_format,
-                    if used_format != from_format {
-                        " (inferred) "
-                    } else {
-                        " "
-                    },
-                    to_formats
-                        .clone()
-                        .into_iter()
-                        .map(|v| v.to_str())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                );
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut prefix = vec![];
+    let from_format = if from_format == Format::Inferred {
+        input
+            .by_ref()
+            .take(PREFIX_LENGTH as u64)
+            .read_to_end(&mut prefix)
+            .unwrap();
+        let found = if prefix.len() < PREFIX_LENGTH {
+            registry.infer(&prefix)
+        } else {
+            registry.infer_prefix(&prefix)
+        };
+        match found.first() {
+            Some(best) => best.format,
+            None => {
+                eprintln!("Couldn't decode! Couldn't infer a format");
+                process::exit(1)
             }
+        }
+    } else {
+        from_format
+    };
 
-            let max_leader_length = to_formats
-                .clone()
-                .into_iter()
-                .map(|s| s.to_str().len())
-                .max()
-                // Safe since we are guaranteed to have at least one element
-                .unwrap();
+    // Errors go to stderr, since stdout may already hold part of the output
+    let stdout = io::stdout();
+    if let Err(e) = registry.transcode(
+        from_format,
+        to_format,
+        &mut prefix.as_slice().chain(input),
+        &mut stdout.lock(),
+    ) {
+        eprintln!("Couldn't convert! {}", e);
+        process::exit(1)
+    }
+}
 
-            let do_leader = (verbosity > 0 && stdout_isatty()) || to_formats.len() > 1;
-            to_formats.into_iter().for_each(|format| {
-                match (encode(format, data.clone()), do_leader) {
-                    (Ok(encoded), true) => println!(
-                        "{}: {: >width$}\"{}\"",
-                        format.to_str(),
-                        "",
-                        encoded,
-                        width = max_leader_length - format.to_str().len(),
-                    ),
-                    // No newline if we're piping a single format
-                    (Ok(encoded), false) => print!("{}", encoded),
-                    (Err(e), true) => println!(
-                        "{}: {: >width$}{}",
-                        format.to_str(),
-                        "",
-                        format!("<encoding failure>: {}", e.err),
-                        width = max_leader_length - format.to_str().len()
-                    ),
-                    (Err(e), false) => print!("<encoding failure>: {}", e.err),
-                }
-            })
+fn run_magic(registry: &Registry, depth: usize, value: Vec<u8>) {
+    let candidates = magic::search(registry, value, depth);
+
+    if stdout_isatty() {
+        println!("\t[magic, up to {} layers]\n", depth);
+    }
+
+    candidates
+        .into_iter()
+        .take(MAGIC_RESULTS)
+        .for_each(|candidate| {
+            println!(
+                "{:.2} {}\n     \"{}\"",
+                candidate.score,
+                candidate.describe(),
+                String::from_utf8_lossy(&candidate.data)
+            )
+        });
+}
+
+fn run_crack(registry: &Registry, cipher: &str, from: &str, value: Vec<u8>) {
+    // Safe since the argument parser validates this value exists
+    let data = match registry.decode(Format::from_str(from).unwrap(), value) {
+        (_, Ok(data)) => data,
+        (_, Err(e)) => {
+            println!("Couldn't decode! {}", e);
+            process::exit(1)
         }
+    };
+    let solutions = match cipher {
+        "caesar" => crack::caesar(&data),
+        "vigenere" => crack::vigenere(&data),
+        _ => Ok(crack::xor(&data)),
+    }
+    .unwrap_or_else(|e| {
+        println!("Couldn't crack! {}", e);
+        process::exit(1)
+    });
+
+    // XOR can leave bytes that aren't text
+    let printable = crack::format(cipher).is_some_and(|format| registry.printable(format));
+
+    if stdout_isatty() {
+        println!(
+            "\t[crack {}, lower scores read more like English]\n",
+            cipher
+        );
+    }
+
+    solutions
+        .into_iter()
+        .take(CRACK_RESULTS)
+        .for_each(|solution| {
+            println!(
+                "{:>8.2} {}\n         \"{}\"",
+                solution.score,
+                solution.describe(),
+                render::shown(&solution.data, printable)
+            )
+        });
+}
+
+fn run_crc_match(registry: &Registry, expected: &str, from: &str, value: Vec<u8>) {
+    let digits = expected
+        .strip_prefix("0x")
+        .unwrap_or(expected)
+        .replace('_', "");
+    let expected = u64::from_str_radix(&digits, 16).unwrap_or_else(|_| {
+        println!("Invalid checksum {}", expected);
+        process::exit(1)
+    });
+    // Safe since the argument parser validates this value exists
+    let data = match registry.decode(Format::from_str(from).unwrap(), value) {
+        (_, Ok(data)) => data,
         (_, Err(e)) => {
-            println!("Couldn't decode! {}", e.err);
+            println!("Couldn't decode! {}", e);
             process::exit(1)
         }
+    };
+
+    let found = checksum::matching(&data, expected);
+    if found.is_empty() {
+        println!("No catalogued CRC comes to {:x}", expected);
+        process::exit(1)
     }
+    found.into_iter().for_each(|(crc, swapped)| {
+        println!(
+            "{}{}",
+            crc.arg,
+            if swapped { " (byte-swapped)" } else { "" }
+        )
+    });
 }
 
-// Defines the order to check codecs in for decoding, encoding,
-// and inferring codecs. Order is significant.
-fn codecs_preferred_order() -> Vec<Box<dyn Codec>> {
-    vec![
-        /*
-           Codecs that can't be assumed must be first
-        */
-        Box::new(codecs::rot13::Rot13Codec {}),
-        /*
-            Inferrable codecs
-        */
-        // Rule out binary before assuming hex
-        Box::new(codecs::binary::BinaryCodec {}),
-        // Rule out hex before assuming base 64
-        Box::new(codecs::hex::HexCodec {}),
-        Box::new(codecs::spelling::SpellingCodec {}),
-        // Rule out base 64 before assuming utf8
-        Box::new(codecs::base64::Base64Codec {}),
-        // Rule out utf8 before assuming it's nothing
-        Box::new(codecs::utf8::Utf8Codec {}),
-        Box::new(codecs::raw::RawCodec {}),
-    ]
+// Exits 2 rather than 1 on errors, so they can't pass for a mismatch
+fn run_expect(
+    registry: &Registry,
+    expected: &str,
+    from: &str,
+    to: &[&str],
+    text: DigestText,
+    value: Vec<u8>,
+) {
+    let to = match to {
+        [to] => Format::from_str(to).unwrap(),
+        _ => {
+            println!("--expect needs exactly one --to format");
+            process::exit(2)
+        }
+    };
+    let (expected, actual) =
+        registry.expect(expected, Format::from_str(from).unwrap(), to, text, value);
+    match (expected, actual) {
+        (Ok(expected), Ok(actual)) if mac::verify(&expected, &actual) => println!("match"),
+        (Ok(_), Ok(_)) => {
+            println!("mismatch");
+            process::exit(1)
+        }
+        (Err(e), _) => {
+            println!("Invalid expected value! {}", e);
+            process::exit(2)
+        }
+        (_, Err(e)) => {
+            println!("Couldn't convert! {}", e);
+            process::exit(2)
+        }
+    }
 }
 
-fn decode(f: Format, value: Vec<u8>) -> (Format, Result<Vec<u8>, Error>) {
-    codecs_preferred_order()
-        .into_iter()
-        .find_map(|codec| {
-            if f == Format::Inferred && codec.inferrable() {
-                if let Ok(result) = codec.decode(value.clone()) {
-                    return Some((codec.format(), Ok(result)));
-                } else {
-                    None
-                }
-            } else {
-                if codec.format() == f {
-                    return Some((f, codec.decode(value.clone())));
-                } else {
-                    return None;
-                }
-            }
-        })
-        .unwrap_or_else(|| {
-            println!("Unsupported format {}", f);
+fn run_pipeline(registry: &Registry, stages: &str, verbosity: u64, value: Vec<u8>) {
+    let pipeline = Pipeline::from_str(stages).unwrap_or_else(|e| {
+        println!("Invalid pipeline! {}", e);
+        process::exit(1)
+    });
+
+    if stdout_isatty() && verbosity > 0 {
+        println!("\t[{}]\n", pipeline);
+    }
+
+    match pipeline.run(registry, value) {
+        Ok(data) => io::stdout().write_all(&data).unwrap(),
+        Err(e) => {
+            println!("Couldn't run pipeline! {}", e);
             process::exit(1)
-        })
+        }
+    }
 }
 
-fn encode(f: Format, data: Vec<u8>) -> Result<String, Error> {
-    codecs_preferred_order()
+fn decode_encode(
+    registry: &Registry,
+    from: &str,
+    transforms: &[Transform],
+    to: Vec<&str>,
+    style: &str,
+    verbosity: u64,
+    value: Vec<u8>,
+) {
+    // These unwrap()s are safe since the argument parser validates these values exist
+    let from_format = Format::from_str(from).unwrap();
+    // TODO dedupe output formats
+    let to_formats = to
         .into_iter()
-        .find_map(|codec| {
-            if codec.format() == f {
-                return Some(codec.encode(data.clone()));
-            } else {
-                None
-            }
+        .map(|v| Format::from_str(v).unwrap())
+        .collect::<Vec<_>>();
+    let style = Style::from_str(style).unwrap();
+
+    // Only worth decoding everything twice if we're going to show it
+    let ranked = if from_format == Format::Inferred && verbosity > 0 {
+        registry
+            .infer(&value)
+            .into_iter()
+            .map(|i| (i.format, i.confidence))
+            .collect()
+    } else {
+        vec![]
+    };
+
+    let (used_format, decoded) = registry.decode(from_format, value);
+    let decoded = decoded.and_then(|data| {
+        transforms.iter().try_fold(data, |data, t| {
+            t.apply(data)
+                .map_err(|e| Error::new(format!("{} failed: {}", t, e.err)))
         })
-        .unwrap_or_else(|| {
-            println!("Unsupported format {}", f);
+    });
+    match (used_format, decoded) {
+        (used_format, Ok(data)) => {
+            let report = Report {
+                source: used_format,
+                inferred: used_format != from_format,
+                ranked,
+                outputs: to_formats
+                    .into_iter()
+                    .map(|format| Output {
+                        format,
+                        printable: registry.printable(format),
+                        result: registry.encode(format, data.clone()),
+                    })
+                    .collect(),
+            };
+            io::stdout()
+                .write_all(&render::render(style, &report, verbosity, stdout_isatty()))
+                .unwrap();
+            if style == Style::Raw {
+                report
+                    .failures()
+                    .into_iter()
+                    .for_each(|(format, e)| eprintln!("{}: <encoding failure>: {}", format, e));
+            }
+        }
+        (used_format, Err(e)) => {
+            io::stdout()
+                .write_all(&render::render_error(style, used_format, &e))
+                .unwrap();
             process::exit(1)
-        })
+        }
+    }
 }
@@ -1,18 +1,106 @@
+use crate::codecs::charset::CHARSETS;
+use crate::codecs::checksum::CATALOG;
+use crate::codecs::error::Error;
 use std::fmt;
+use std::str::FromStr;
+
+// Every radix a number can be converted to or from, starting at 2
+const RADIX_ARGS: [&str; 35] = [
+    "b2", "b3", "b4", "b5", "b6", "b7", "b8", "b9", "b10", "b11", "b12", "b13", "b14", "b15",
+    "b16", "b17", "b18", "b19", "b20", "b21", "b22", "b23", "b24", "b25", "b26", "b27", "b28",
+    "b29", "b30", "b31", "b32", "b33", "b34", "b35", "b36",
+];
+const RADIX_NAMES: [&str; 35] = [
+    "base 2", "base 3", "base 4", "base 5", "base 6", "base 7", "base 8", "base 9", "base 10",
+    "base 11", "base 12", "base 13", "base 14", "base 15", "base 16", "base 17", "base 18",
+    "base 19", "base 20", "base 21", "base 22", "base 23", "base 24", "base 25", "base 26",
+    "base 27", "base 28", "base 29", "base 30", "base 31", "base 32", "base 33", "base 34",
+    "base 35", "base 36",
+];
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum Format {
-    B2,
-    B8,
-    B10,
-    B16,
+    // Any radix from 2 to 36
+    Radix(u8),
     Ascii,
     Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+    // Index into the charset catalog
+    Charset(u8),
+    ShiftJis,
+    EucJp,
+    Iso2022Jp,
+    Gbk,
+    Gb18030,
+    Big5,
+    EucKr,
     Hex,
+    Xxd,
+    Hexdump,
+    Base32,
+    Base32Hex,
+    Crockford,
+    CrockfordCheck,
+    ZBase32,
     Base64,
+    Base64Url,
+    Base64Unpadded,
+    Base64Mime,
+    Base64Lenient,
+    Ascii85,
+    Btoa,
+    Z85,
+    Url,
+    UrlPath,
+    UrlQuery,
+    UrlForm,
+    UrlRecursive,
+    CString,
+    JsonString,
+    RustString,
+    PythonString,
+    JsString,
+    Shell,
     Binary,
+    Gzip,
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+    Blake2b,
+    Blake2s,
+    Blake3,
+    HmacMd5,
+    HmacSha1,
+    HmacSha224,
+    HmacSha256,
+    HmacSha384,
+    HmacSha512,
+    // Index into the CRC catalog
+    Crc(u8),
+    Adler32,
+    Fletcher16,
+    Fletcher32,
+    Luhn,
+    Verhoeff,
     Spelling,
     Rot13,
+    Caesar,
+    Atbash,
+    Affine,
+    Vigenere,
+    Beaufort,
+    Substitution,
+    Xor,
     Raw,
     Inferred,
 }
@@ -20,42 +108,215 @@ pub enum Format {
 impl Format {
     pub fn all_variants() -> Vec<&'static str> {
         vec![
-            "utf8", "hex", "base64", "binary", "spelling", "raw", "rot13",
+            "utf8",
+            "utf16le",
+            "utf16be",
+            "utf32le",
+            "utf32be",
+            "shiftjis",
+            "eucjp",
+            "iso2022jp",
+            "gbk",
+            "gb18030",
+            "big5",
+            "euckr",
+            "hex",
+            "xxd",
+            "hexdump",
+            "base32",
+            "base32hex",
+            "crockford",
+            "crockford-check",
+            "zbase32",
+            "base64",
+            "base64url",
+            "base64-unpadded",
+            "base64-mime",
+            "base64-lenient",
+            "ascii85",
+            "btoa",
+            "z85",
+            "url",
+            "url-path",
+            "url-query",
+            "url-form",
+            "url-recursive",
+            "c-string",
+            "json-string",
+            "rust-string",
+            "python-string",
+            "js-string",
+            "shell",
+            "binary",
+            "gzip",
+            "md5",
+            "sha1",
+            "sha224",
+            "sha256",
+            "sha384",
+            "sha512",
+            "sha3-224",
+            "sha3-256",
+            "sha3-384",
+            "sha3-512",
+            "blake2b",
+            "blake2s",
+            "blake3",
+            "hmac-md5",
+            "hmac-sha1",
+            "hmac-sha224",
+            "hmac-sha256",
+            "hmac-sha384",
+            "hmac-sha512",
+            "adler32",
+            "fletcher16",
+            "fletcher32",
+            "luhn",
+            "verhoeff",
+            "spelling",
+            "raw",
+            "rot13",
+            "caesar",
+            "atbash",
+            "affine",
+            "vigenere",
+            "beaufort",
+            "substitution",
+            "xor",
         ]
+        .into_iter()
+        .chain(CHARSETS.iter().map(|charset| charset.arg))
+        .chain(CATALOG.iter().map(|crc| crc.arg))
+        .chain(RADIX_ARGS.iter().cloned())
+        .collect()
     }
 
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "b2" => Some(Self::B2),
-            "b8" => Some(Self::B8),
-            "b10" => Some(Self::B10),
-            "b16" => Some(Self::B16),
-            "ascii" => Some(Self::Ascii),
-            "utf8" => Some(Self::Utf8),
-            "hex" => Some(Self::Hex),
-            "base64" => Some(Self::Base64),
-            "binary" => Some(Self::Binary),
-            "spelling" => Some(Self::Spelling),
-            "rot13" => Some(Self::Rot13),
-            "raw" => Some(Self::Raw),
-            "__infer" => Some(Self::Inferred),
-            _ => None,
-        }
+    pub fn from_radix(radix: u32) -> Self {
+        Self::Radix(radix as u8)
+    }
+
+    // Digests and MACs, whose output is bytes however it's written
+    pub fn hashed(self) -> bool {
+        matches!(
+            self,
+            Self::Md5
+                | Self::Sha1
+                | Self::Sha224
+                | Self::Sha256
+                | Self::Sha384
+                | Self::Sha512
+                | Self::Sha3_224
+                | Self::Sha3_256
+                | Self::Sha3_384
+                | Self::Sha3_512
+                | Self::Blake2b
+                | Self::Blake2s
+                | Self::Blake3
+                | Self::HmacMd5
+                | Self::HmacSha1
+                | Self::HmacSha224
+                | Self::HmacSha256
+                | Self::HmacSha384
+                | Self::HmacSha512
+        )
+    }
+
+    // CRCs and the like, whose value is always written in hex
+    pub fn checksum(self) -> bool {
+        matches!(
+            self,
+            Self::Crc(_) | Self::Adler32 | Self::Fletcher16 | Self::Fletcher32
+        )
+    }
+
+    // The name used for this format on the command line
+    pub fn to_arg(self) -> &'static str {
+        Self::all_variants()
+            .into_iter()
+            .find(|arg| Self::from_str(arg).ok() == Some(self))
+            .unwrap_or("__infer")
     }
 
-    pub fn to_str(&self) -> &'static str {
+    pub fn to_str(self) -> &'static str {
         match self {
-            Self::B2 => "base 2",
-            Self::B8 => "base 8",
-            Self::B10 => "base 10",
-            Self::B16 => "base 16",
+            Self::Radix(r) => RADIX_NAMES[r as usize - 2],
             Self::Ascii => "ascii",
             Self::Utf8 => "utf8",
+            Self::Utf16Le => "utf16le",
+            Self::Utf16Be => "utf16be",
+            Self::Utf32Le => "utf32le",
+            Self::Utf32Be => "utf32be",
+            Self::Charset(i) => CHARSETS[i as usize].name,
+            Self::ShiftJis => "shift_jis",
+            Self::EucJp => "euc-jp",
+            Self::Iso2022Jp => "iso-2022-jp",
+            Self::Gbk => "gbk",
+            Self::Gb18030 => "gb18030",
+            Self::Big5 => "big5",
+            Self::EucKr => "euc-kr",
             Self::Hex => "hex",
+            Self::Xxd => "xxd",
+            Self::Hexdump => "hexdump -C",
+            Self::Base32 => "base 32",
+            Self::Base32Hex => "base 32 hex",
+            Self::Crockford => "crockford base 32",
+            Self::CrockfordCheck => "crockford base 32 (checked)",
+            Self::ZBase32 => "z-base-32",
             Self::Base64 => "base 64",
+            Self::Base64Url => "base 64 url",
+            Self::Base64Unpadded => "base 64 (unpadded)",
+            Self::Base64Mime => "base 64 mime",
+            Self::Base64Lenient => "base 64 (lenient)",
+            Self::Ascii85 => "ascii85",
+            Self::Btoa => "btoa",
+            Self::Z85 => "z85",
+            Self::Url => "url",
+            Self::UrlPath => "url path segment",
+            Self::UrlQuery => "url query component",
+            Self::UrlForm => "url form",
+            Self::UrlRecursive => "url (recursive)",
+            Self::CString => "c string",
+            Self::JsonString => "json string",
+            Self::RustString => "rust string",
+            Self::PythonString => "python string",
+            Self::JsString => "javascript string",
+            Self::Shell => "shell quoted",
             Self::Binary => "binary",
+            Self::Gzip => "gzip",
+            Self::Md5 => "md5",
+            Self::Sha1 => "sha-1",
+            Self::Sha224 => "sha-224",
+            Self::Sha256 => "sha-256",
+            Self::Sha384 => "sha-384",
+            Self::Sha512 => "sha-512",
+            Self::Sha3_224 => "sha3-224",
+            Self::Sha3_256 => "sha3-256",
+            Self::Sha3_384 => "sha3-384",
+            Self::Sha3_512 => "sha3-512",
+            Self::Blake2b => "blake2b",
+            Self::Blake2s => "blake2s",
+            Self::Blake3 => "blake3",
+            Self::HmacMd5 => "hmac-md5",
+            Self::HmacSha1 => "hmac-sha-1",
+            Self::HmacSha224 => "hmac-sha-224",
+            Self::HmacSha256 => "hmac-sha-256",
+            Self::HmacSha384 => "hmac-sha-384",
+            Self::HmacSha512 => "hmac-sha-512",
+            Self::Crc(i) => CATALOG[i as usize].name,
+            Self::Adler32 => "adler-32",
+            Self::Fletcher16 => "fletcher-16",
+            Self::Fletcher32 => "fletcher-32",
+            Self::Luhn => "luhn",
+            Self::Verhoeff => "verhoeff",
             Self::Spelling => "spelling",
             Self::Rot13 => "rot13",
+            Self::Caesar => "caesar",
+            Self::Atbash => "atbash",
+            Self::Affine => "affine",
+            Self::Vigenere => "vigenère",
+            Self::Beaufort => "beaufort",
+            Self::Substitution => "substitution",
+            Self::Xor => "xor",
             Self::Raw => "raw bytes",
             // This shouldn't really happen, since if we ever have an inferred enum
             // we should be converting it to a relevant one
@@ -70,3 +331,147 @@ impl fmt::Display for Format {
         write!(f, "{}", s)
     }
 }
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "ascii" => Ok(Self::Ascii),
+            "utf8" => Ok(Self::Utf8),
+            "utf16le" => Ok(Self::Utf16Le),
+            "utf16be" => Ok(Self::Utf16Be),
+            "utf32le" => Ok(Self::Utf32Le),
+            "utf32be" => Ok(Self::Utf32Be),
+            "shiftjis" => Ok(Self::ShiftJis),
+            "eucjp" => Ok(Self::EucJp),
+            "iso2022jp" => Ok(Self::Iso2022Jp),
+            "gbk" => Ok(Self::Gbk),
+            "gb18030" => Ok(Self::Gb18030),
+            "big5" => Ok(Self::Big5),
+            "euckr" => Ok(Self::EucKr),
+            "hex" => Ok(Self::Hex),
+            "xxd" => Ok(Self::Xxd),
+            "hexdump" => Ok(Self::Hexdump),
+            "base32" => Ok(Self::Base32),
+            "base32hex" => Ok(Self::Base32Hex),
+            "crockford" => Ok(Self::Crockford),
+            "crockford-check" => Ok(Self::CrockfordCheck),
+            "zbase32" => Ok(Self::ZBase32),
+            "base64" => Ok(Self::Base64),
+            "base64url" => Ok(Self::Base64Url),
+            "base64-unpadded" => Ok(Self::Base64Unpadded),
+            "base64-mime" => Ok(Self::Base64Mime),
+            "base64-lenient" => Ok(Self::Base64Lenient),
+            "ascii85" => Ok(Self::Ascii85),
+            "btoa" => Ok(Self::Btoa),
+            "z85" => Ok(Self::Z85),
+            "url" => Ok(Self::Url),
+            "url-path" => Ok(Self::UrlPath),
+            "url-query" => Ok(Self::UrlQuery),
+            "url-form" => Ok(Self::UrlForm),
+            "url-recursive" => Ok(Self::UrlRecursive),
+            "c-string" => Ok(Self::CString),
+            "json-string" => Ok(Self::JsonString),
+            "rust-string" => Ok(Self::RustString),
+            "python-string" => Ok(Self::PythonString),
+            "js-string" => Ok(Self::JsString),
+            "shell" => Ok(Self::Shell),
+            "binary" => Ok(Self::Binary),
+            "gzip" => Ok(Self::Gzip),
+            "md5" => Ok(Self::Md5),
+            "sha1" => Ok(Self::Sha1),
+            "sha224" => Ok(Self::Sha224),
+            "sha256" => Ok(Self::Sha256),
+            "sha384" => Ok(Self::Sha384),
+            "sha512" => Ok(Self::Sha512),
+            "sha3-224" => Ok(Self::Sha3_224),
+            "sha3-256" => Ok(Self::Sha3_256),
+            "sha3-384" => Ok(Self::Sha3_384),
+            "sha3-512" => Ok(Self::Sha3_512),
+            "blake2b" => Ok(Self::Blake2b),
+            "blake2s" => Ok(Self::Blake2s),
+            "blake3" => Ok(Self::Blake3),
+            "hmac-md5" => Ok(Self::HmacMd5),
+            "hmac-sha1" => Ok(Self::HmacSha1),
+            "hmac-sha224" => Ok(Self::HmacSha224),
+            "hmac-sha256" => Ok(Self::HmacSha256),
+            "hmac-sha384" => Ok(Self::HmacSha384),
+            "hmac-sha512" => Ok(Self::HmacSha512),
+            "adler32" => Ok(Self::Adler32),
+            "fletcher16" => Ok(Self::Fletcher16),
+            "fletcher32" => Ok(Self::Fletcher32),
+            "luhn" => Ok(Self::Luhn),
+            "verhoeff" => Ok(Self::Verhoeff),
+            "spelling" => Ok(Self::Spelling),
+            "rot13" => Ok(Self::Rot13),
+            "caesar" => Ok(Self::Caesar),
+            "atbash" => Ok(Self::Atbash),
+            "affine" => Ok(Self::Affine),
+            "vigenere" => Ok(Self::Vigenere),
+            "beaufort" => Ok(Self::Beaufort),
+            "substitution" => Ok(Self::Substitution),
+            "xor" => Ok(Self::Xor),
+            "raw" => Ok(Self::Raw),
+            "__infer" => Ok(Self::Inferred),
+            s => RADIX_ARGS
+                .iter()
+                .position(|&arg| arg == s)
+                .map(|i| Self::from_radix(i as u32 + 2))
+                .or_else(|| {
+                    CHARSETS
+                        .iter()
+                        .position(|charset| charset.arg == s)
+                        .map(|i| Self::Charset(i as u8))
+                })
+                .or_else(|| {
+                    CATALOG
+                        .iter()
+                        .position(|crc| crc.arg == s)
+                        .map(|i| Self::Crc(i as u8))
+                })
+                .ok_or_else(|| Error::new(format!("Unknown format {}", s))),
+        }
+    }
+}
+
+// How to display the output
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Style {
+    Text,
+    Json,
+    Table,
+    Raw,
+}
+
+impl Style {
+    pub fn all_variants() -> Vec<&'static str> {
+        vec!["text", "json", "table", "raw"]
+    }
+}
+
+impl FromStr for Style {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "table" => Ok(Self::Table),
+            "raw" => Ok(Self::Raw),
+            _ => Err(Error::new(format!("Unknown style {}", s))),
+        }
+    }
+}
+
+#[test]
+fn radix() {
+    // Each base has one format, however it was arrived at
+    for radix in 2..=36 {
+        let format = Format::from_radix(radix);
+        let arg = format!("b{}", radix);
+        assert_eq!(format, arg.parse::<Format>().unwrap());
+        assert_eq!(arg, format.to_arg());
+        assert_eq!(format!("base {}", radix), format.to_str());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::codecs::error::Error;
+use crate::options::Format;
+use crate::registry::Registry;
+use crate::transform::Transform;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Stage {
+    // Decode the bytes from this format
+    From(Format),
+    // Encode the bytes into this format
+    To(Format),
+    // Rewrite the text
+    Apply(Transform),
+}
+
+// A sequence of stages, where each stage's output is the next stage's input
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Pipeline {
+    pub stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    pub fn run(&self, registry: &Registry, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        self.stages
+            .iter()
+            .enumerate()
+            .try_fold(data, |data, (i, stage)| {
+                match stage {
+                    Stage::From(f) => registry.decode(*f, data).1,
+                    Stage::To(f) => registry.encode(*f, data),
+                    Stage::Apply(t) => t.apply(data),
+                }
+                .map_err(|e| Error {
+                    err: format!("stage {} ({}) failed: {}", i + 1, stage, e.err),
+                    offset: e.offset,
+                })
+            })
+    }
+}
+
+impl FromStr for Pipeline {
+    type Err = Error;
+
+    // Parses stages separated by |, such as "from:base64 | from:hex | rot13 | to:utf8".
+    // A stage without a direction encodes, since that is how ciphers like
+    // rot13 are applied, unless it names a text transform like snake.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        s.split('|')
+            .map(str::trim)
+            .map(|stage| {
+                let (make, name): (fn(Format) -> Stage, &str) = match stage.split_once(':') {
+                    Some(("from", name)) => (Stage::From, name),
+                    Some(("to", name)) => (Stage::To, name),
+                    Some((dir, _)) => {
+                        return Err(Error::new(format!(
+                            "Unknown direction {} in stage {}",
+                            dir, stage
+                        )))
+                    }
+                    None => match Transform::from_str(stage) {
+                        Ok(t) => return Ok(Stage::Apply(t)),
+                        Err(_) => (Stage::To, stage),
+                    },
+                };
+                let name = name.trim();
+                match Format::from_str(name) {
+                    Ok(f) if Format::all_variants().contains(&name) => Ok(make(f)),
+                    _ => Err(Error::new(format!(
+                        "Unknown format {} in stage {}",
+                        name, stage
+                    ))),
+                }
+            })
+            .collect::<Result<Vec<Stage>, Error>>()
+            .map(|stages| Self { stages })
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::From(format) => write!(f, "from {}", format),
+            Self::To(format) => write!(f, "to {}", format),
+            Self::Apply(transform) => write!(f, "{}", transform),
+        }
+    }
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let stages = self
+            .stages
+            .iter()
+            .map(|stage| stage.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}", stages.join(" | "))
+    }
+}
+
+#[test]
+fn parse() {
+    assert_eq!(
+        Ok(Pipeline {
+            stages: vec![
+                Stage::From(Format::Base64),
+                Stage::From(Format::Hex),
+                Stage::To(Format::Rot13),
+                Stage::To(Format::Utf8),
+            ]
+        }),
+        Pipeline::from_str("from:base64 | from:hex | rot13 | to:utf8")
+    );
+    assert_eq!(
+        Ok(Pipeline {
+            stages: vec![
+                Stage::From(Format::Hex),
+                Stage::Apply(Transform::Snake),
+                Stage::To(Format::Utf8),
+            ]
+        }),
+        Pipeline::from_str("from:hex | snake | to:utf8")
+    );
+    assert_eq!(
+        Err(Error::new(
+            "Unknown format nope in stage to:nope".to_string()
+        )),
+        Pipeline::from_str("from:hex|to:nope")
+    );
+    assert_eq!(
+        Err(Error::new(
+            "Unknown direction via in stage via:hex".to_string()
+        )),
+        Pipeline::from_str("via:hex")
+    );
+}
+
+#[test]
+fn run() {
+    let registry = Registry::new();
+    let pipeline = Pipeline::from_str("from:base64 | from:hex | rot13 | to:base64").unwrap();
+    // base64 of the hex of "uryyb"
+    assert_eq!(
+        Ok("aGVsbG8=".as_bytes().to_vec()),
+        pipeline.run(&registry, "NzU3Mjc5Nzk2Mg==".as_bytes().to_vec())
+    );
+    assert_eq!(
+        Err(Error::new(
+            "stage 2 (from hex) failed: Invalid hex character z".to_string()
+        )),
+        pipeline.run(&registry, "eno=".as_bytes().to_vec())
+    );
+
+    // Hex of "Hello World"
+    let pipeline = Pipeline::from_str("from:hex | snake | reverse | to:base64").unwrap();
+    assert_eq!(
+        Ok("ZGxyb3dfb2xsZWg=".as_bytes().to_vec()),
+        pipeline.run(&registry, "48656c6c6f20576f726c64".as_bytes().to_vec())
+    );
+}
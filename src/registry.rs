@@ -0,0 +1,531 @@
+use crate::codecs;
+use crate::codecs::ascii85::Ascii85Variant;
+use crate::codecs::base32::Base32Variant;
+use crate::codecs::base64::Base64Variant;
+use crate::codecs::charset::CHARSETS;
+use crate::codecs::checksum::{ChecksumKind, CATALOG};
+use crate::codecs::cipher::Cipher;
+use crate::codecs::cjk::CjkEncoding;
+use crate::codecs::codec::Codec;
+use crate::codecs::digest::{Algorithm, DigestText};
+use crate::codecs::error::Error;
+use crate::codecs::escape::EscapeSyntax;
+use crate::codecs::hexdump::DumpLayout;
+use crate::codecs::mac::MacAlgorithm;
+use crate::codecs::stream;
+use crate::codecs::stream::EncodeWriter;
+use crate::codecs::unicode::UnicodeVariant;
+use crate::codecs::url::UrlMode;
+use crate::options::Format;
+use std::collections::HashSet;
+use std::io::{Read, Write};
+
+// Every codec rc knows about
+pub struct Registry {
+    codecs: Vec<Box<dyn Codec>>,
+}
+
+// A format the input could be in, and what it decodes to
+pub struct Interpretation {
+    pub format: Format,
+    pub confidence: f64,
+    pub data: Vec<u8>,
+}
+
+impl Registry {
+    // Defines the order to check codecs in for decoding, encoding,
+    // and inferring codecs. Order is significant.
+    pub fn new() -> Self {
+        let mut codecs: Vec<Box<dyn Codec>> = vec![
+            /*
+               Codecs that can't be assumed must be first
+            */
+            Box::new(codecs::cipher::CipherCodec {
+                cipher: Cipher::Rot13,
+            }),
+            Box::new(codecs::cipher::CipherCodec {
+                cipher: Cipher::Caesar(3),
+            }),
+            Box::new(codecs::cipher::CipherCodec {
+                cipher: Cipher::Atbash,
+            }),
+            Box::new(codecs::cipher::CipherCodec {
+                cipher: Cipher::Affine(None),
+            }),
+            Box::new(codecs::cipher::CipherCodec {
+                cipher: Cipher::Vigenere(None),
+            }),
+            Box::new(codecs::cipher::CipherCodec {
+                cipher: Cipher::Beaufort(None),
+            }),
+            Box::new(codecs::cipher::CipherCodec {
+                cipher: Cipher::Substitution(None),
+            }),
+            Box::new(codecs::xor::XorCodec { key: None }),
+            // Nearly anything is valid Shift_JIS or GBK
+            Box::new(codecs::cjk::CjkCodec {
+                encoding: CjkEncoding::ShiftJis,
+                replace: false,
+            }),
+            Box::new(codecs::cjk::CjkCodec {
+                encoding: CjkEncoding::EucJp,
+                replace: false,
+            }),
+            Box::new(codecs::cjk::CjkCodec {
+                encoding: CjkEncoding::Iso2022Jp,
+                replace: false,
+            }),
+            Box::new(codecs::cjk::CjkCodec {
+                encoding: CjkEncoding::Gbk,
+                replace: false,
+            }),
+            Box::new(codecs::cjk::CjkCodec {
+                encoding: CjkEncoding::Gb18030,
+                replace: false,
+            }),
+            Box::new(codecs::cjk::CjkCodec {
+                encoding: CjkEncoding::Big5,
+                replace: false,
+            }),
+            Box::new(codecs::cjk::CjkCodec {
+                encoding: CjkEncoding::EucKr,
+                replace: false,
+            }),
+            Box::new(codecs::base32::Base32Codec {
+                variant: Base32Variant::ExtendedHex,
+            }),
+            Box::new(codecs::base32::Base32Codec {
+                variant: Base32Variant::Crockford,
+            }),
+            Box::new(codecs::base32::Base32Codec {
+                variant: Base32Variant::CrockfordCheck,
+            }),
+            Box::new(codecs::base32::Base32Codec {
+                variant: Base32Variant::ZBase32,
+            }),
+            Box::new(codecs::ascii85::Ascii85Codec {
+                variant: Ascii85Variant::Z85,
+            }),
+            Box::new(codecs::base64::Base64Codec {
+                variant: Base64Variant::Lenient,
+            }),
+            Box::new(codecs::url::UrlCodec {
+                mode: UrlMode::Uri,
+                recursive: false,
+            }),
+            Box::new(codecs::url::UrlCodec {
+                mode: UrlMode::PathSegment,
+                recursive: false,
+            }),
+            Box::new(codecs::url::UrlCodec {
+                mode: UrlMode::Query,
+                recursive: false,
+            }),
+            Box::new(codecs::url::UrlCodec {
+                mode: UrlMode::Form,
+                recursive: false,
+            }),
+            Box::new(codecs::url::UrlCodec {
+                mode: UrlMode::Uri,
+                recursive: true,
+            }),
+            Box::new(codecs::escape::EscapeCodec {
+                syntax: EscapeSyntax::C,
+            }),
+            Box::new(codecs::escape::EscapeCodec {
+                syntax: EscapeSyntax::Json,
+            }),
+            Box::new(codecs::escape::EscapeCodec {
+                syntax: EscapeSyntax::Rust,
+            }),
+            Box::new(codecs::escape::EscapeCodec {
+                syntax: EscapeSyntax::Python,
+            }),
+            Box::new(codecs::escape::EscapeCodec {
+                syntax: EscapeSyntax::JavaScript,
+            }),
+            Box::new(codecs::escape::EscapeCodec {
+                syntax: EscapeSyntax::Shell,
+            }),
+            /*
+                Inferrable codecs
+            */
+            // Framed formats are unambiguous, so they go first
+            Box::new(codecs::gzip::GzipCodec {}),
+            Box::new(codecs::ascii85::Ascii85Codec {
+                variant: Ascii85Variant::Adobe,
+            }),
+            Box::new(codecs::ascii85::Ascii85Codec {
+                variant: Ascii85Variant::Btoa,
+            }),
+            // Rule out binary before assuming hex
+            Box::new(codecs::binary::BinaryCodec {}),
+            // Rule out hex before assuming base 64
+            Box::new(codecs::hex::HexCodec::default()),
+            // Dumps are hex with offsets and gutters, so plain hex is hex
+            Box::new(codecs::hexdump::HexdumpCodec {
+                layout: DumpLayout::Xxd,
+                width: 16,
+                group: 2,
+            }),
+            Box::new(codecs::hexdump::HexdumpCodec {
+                layout: DumpLayout::Canonical,
+                width: 16,
+                group: 8,
+            }),
+            Box::new(codecs::spelling::SpellingCodec {}),
+            // Base 32 is a subset of the base 64 alphabet, and all
+            // hex digits are also base 32 digits
+            Box::new(codecs::base32::Base32Codec {
+                variant: Base32Variant::Standard,
+            }),
+            // Rule out base 64 before assuming utf8
+            Box::new(codecs::base64::Base64Codec {
+                variant: Base64Variant::Standard,
+            }),
+            // Line-wrapped, then unpadded base 64. These only accept canonical
+            // trailing bits, so plain words rarely get mistaken for them
+            Box::new(codecs::base64::Base64Codec {
+                variant: Base64Variant::Mime,
+            }),
+            Box::new(codecs::base64::Base64Codec {
+                variant: Base64Variant::Unpadded,
+            }),
+            Box::new(codecs::base64::Base64Codec {
+                variant: Base64Variant::UrlSafe,
+            }),
+            // Text with every other byte zero is valid utf8 too, but unlikely
+            Box::new(codecs::unicode::UnicodeCodec {
+                variant: UnicodeVariant::Utf32Le,
+                bom: false,
+            }),
+            Box::new(codecs::unicode::UnicodeCodec {
+                variant: UnicodeVariant::Utf32Be,
+                bom: false,
+            }),
+            Box::new(codecs::unicode::UnicodeCodec {
+                variant: UnicodeVariant::Utf16Le,
+                bom: false,
+            }),
+            Box::new(codecs::unicode::UnicodeCodec {
+                variant: UnicodeVariant::Utf16Be,
+                bom: false,
+            }),
+            // Rule out utf8 before assuming it's nothing
+            Box::new(codecs::utf8::Utf8Codec {}),
+            Box::new(codecs::raw::RawCodec {}),
+        ];
+        // Legacy charsets last, since a format that fits says more
+        codecs.extend(
+            (0..CHARSETS.len() as u8).map(|charset| {
+                Box::new(codecs::charset::CharsetCodec { charset }) as Box<dyn Codec>
+            }),
+        );
+        // Number bases can't be assumed either, but there are a lot of them
+        codecs.splice(
+            0..0,
+            (2..=36).map(|radix| Box::new(codecs::number::NumberCodec { radix }) as Box<dyn Codec>),
+        );
+        // Digests and checksums only encode, so inference never tries them
+        codecs.splice(
+            0..0,
+            Algorithm::all().into_iter().map(|algorithm| {
+                Box::new(codecs::digest::DigestCodec {
+                    algorithm,
+                    text: DigestText::Hex,
+                }) as Box<dyn Codec>
+            }),
+        );
+        // HMACs need a key before they're any use; see register
+        codecs.splice(
+            0..0,
+            MacAlgorithm::all().into_iter().map(|algorithm| {
+                Box::new(codecs::mac::HmacCodec {
+                    algorithm,
+                    key: None,
+                    text: DigestText::Hex,
+                }) as Box<dyn Codec>
+            }),
+        );
+        codecs.splice(
+            0..0,
+            (0..CATALOG.len() as u8)
+                .map(ChecksumKind::Crc)
+                .chain(vec![
+                    ChecksumKind::Adler32,
+                    ChecksumKind::Fletcher16,
+                    ChecksumKind::Fletcher32,
+                    ChecksumKind::Luhn,
+                    ChecksumKind::Verhoeff,
+                ])
+                .map(|kind| Box::new(codecs::checksum::ChecksumCodec { kind }) as Box<dyn Codec>),
+        );
+        Self { codecs }
+    }
+
+    // Adds a codec, replacing the one already there for its format
+    pub fn register(&mut self, codec: Box<dyn Codec>) {
+        match self
+            .codecs
+            .iter()
+            .position(|existing| existing.format() == codec.format())
+        {
+            Some(i) => self.codecs[i] = codec,
+            None => self.codecs.push(codec),
+        }
+    }
+
+    pub fn codecs(&self) -> &[Box<dyn Codec>] {
+        &self.codecs
+    }
+
+    pub fn get(&self, f: Format) -> Option<&dyn Codec> {
+        self.codecs
+            .iter()
+            .find(|codec| codec.format() == f)
+            .map(|codec| codec.as_ref())
+    }
+
+    // Decodes the value with every inferrable codec that accepts it,
+    // most confident first
+    pub fn infer(&self, value: &[u8]) -> Vec<Interpretation> {
+        self.rank(|_| Some(value))
+    }
+
+    // Like infer, but for the start of a longer input. Only codecs with
+    // framing take part, each judged on the whole groups in the prefix.
+    pub fn infer_prefix(&self, prefix: &[u8]) -> Vec<Interpretation> {
+        self.rank(|codec| {
+            codec
+                .framing()
+                .map(|framing| stream::aligned(prefix, &framing))
+                .filter(|aligned| !aligned.is_empty())
+        })
+    }
+
+    // Decodes what sample gives each inferrable codec, most confident first
+    fn rank<'a>(&self, sample: impl Fn(&dyn Codec) -> Option<&'a [u8]>) -> Vec<Interpretation> {
+        let mut found = self
+            .codecs
+            .iter()
+            .filter(|codec| codec.inferrable())
+            .filter_map(|codec| {
+                let value = sample(codec.as_ref())?;
+                codec
+                    .decode(value.to_vec())
+                    .ok()
+                    .map(|data| Interpretation {
+                        format: codec.format(),
+                        confidence: codec.confidence(value, &data),
+                        data,
+                    })
+            })
+            // A confidence that isn't a number can't be ranked
+            .filter(|i| !i.confidence.is_nan())
+            .collect::<Vec<_>>();
+        // The sort is stable, so ties keep the preferred order
+        found.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        // Variants that agree on the data aren't different interpretations
+        let mut seen = HashSet::new();
+        found.retain(|i| seen.insert(i.data.clone()));
+        // Nearly every charset decodes anything, so only the likeliest counts
+        let mut charset = false;
+        found.retain(|i| match i.format {
+            Format::Charset(_) => !std::mem::replace(&mut charset, true),
+            _ => true,
+        });
+        found
+    }
+
+    // Decodes the value from f, or from the most likely format if f is
+    // Format::Inferred, and says which format was used
+    pub fn decode(&self, f: Format, value: Vec<u8>) -> (Format, Result<Vec<u8>, Error>) {
+        if f == Format::Inferred {
+            match self.infer(&value).into_iter().next() {
+                Some(best) => (best.format, Ok(best.data)),
+                None => (f, Err(Error::new("Couldn't infer a format".to_string()))),
+            }
+        } else {
+            match self.get(f) {
+                Some(codec) => (f, codec.decode(value)),
+                None => (f, Err(Self::unsupported(f))),
+            }
+        }
+    }
+
+    pub fn encode(&self, f: Format, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        match self.get(f) {
+            Some(codec) => codec.encode(data),
+            None => Err(Self::unsupported(f)),
+        }
+    }
+
+    // Decodes a value written as "format:value", like "hex:00ff", falling
+    // back to the default format when there's no known format prefix
+    pub fn decode_spec(&self, spec: &str, default: Format) -> Result<Vec<u8>, Error> {
+        let (format, value) = spec
+            .split_once(':')
+            .and_then(|(arg, value)| {
+                arg.parse::<Format>()
+                    .ok()
+                    .filter(|f| *f != Format::Inferred)
+                    .map(|f| (f, value))
+            })
+            .unwrap_or((default, spec));
+        self.decode(format, value.as_bytes().to_vec()).1
+    }
+
+    // The expected and actual sides of --expect, ready to compare. Digests,
+    // MACs and checksums are compared as bytes, so hex case and the like
+    // don't matter, formats that decode are compared decoded, and check
+    // digits are compared as they're written
+    pub fn expect(
+        &self,
+        expected: &str,
+        from: Format,
+        to: Format,
+        text: DigestText,
+        value: Vec<u8>,
+    ) -> (Result<Vec<u8>, Error>, Result<Vec<u8>, Error>) {
+        let written = if to.hashed() {
+            Some(text.format())
+        } else if to.checksum() {
+            Some(Format::Hex)
+        } else if self.get(to).is_some_and(|codec| codec.decodable()) {
+            Some(to)
+        } else {
+            None
+        };
+        let expected = match written {
+            Some(written) => self.decode_spec(expected, written),
+            None => Ok(expected.as_bytes().to_vec()),
+        };
+        let actual = self
+            .decode(from, value)
+            .1
+            .and_then(|data| self.encode(to, data))
+            .and_then(|encoded| match written {
+                Some(written) => self.decode(written, encoded).1,
+                None => Ok(encoded),
+            });
+        (expected, actual)
+    }
+
+    // Whether f encodes to text that can be shown as is
+    pub fn printable(&self, f: Format) -> bool {
+        self.get(f).is_none_or(|codec| codec.printable())
+    }
+
+    // Converts input from one format to another a piece at a time, so it
+    // never has to be held in memory all at once
+    pub fn transcode(
+        &self,
+        from: Format,
+        to: Format,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+    ) -> Result<(), Error> {
+        let (decoder, encoder) = match (self.get(from), self.get(to)) {
+            (Some(decoder), Some(encoder)) => (decoder, encoder),
+            (None, _) => return Err(Self::unsupported(from)),
+            (_, None) => return Err(Self::unsupported(to)),
+        };
+        let mut writer = EncodeWriter::new(encoder, output);
+        let result = decoder.decode_stream(input, &mut writer);
+        writer.finish(result)
+    }
+
+    fn unsupported(f: Format) -> Error {
+        Error::new(format!("Unsupported format {}", f))
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn ranked_inference() {
+    let registry = Registry::new();
+    let formats = |s: &str| {
+        registry
+            .infer(s.as_bytes())
+            .into_iter()
+            .map(|i| i.format)
+            .collect::<Vec<_>>()
+    };
+    // Hex of "hello"
+    assert_eq!(Format::Hex, formats("68656c6c6f")[0]);
+    // Valid base 64 and a readable word, but even-length hex is hex
+    let deadbeef = formats("deadbeef");
+    assert_eq!(Format::Hex, deadbeef[0]);
+    assert!(deadbeef.contains(&Format::Utf8));
+    assert!(deadbeef.contains(&Format::Base64));
+    assert_eq!(Format::Hex, formats("DEADBEEF")[0]);
+    assert_eq!(Format::Base64, formats("aGVsbG8gd29ybGQ=")[0]);
+    // Uppercase words are valid base 32, but not padded like it
+    assert_eq!(Format::Utf8, formats("HELLO")[0]);
+    assert_eq!(Format::Utf8, formats("NASA")[0]);
+    assert_eq!(Format::Base32, formats("NBSWY3DP")[0]);
+    // Digits between separators, but not hex ones
+    assert_eq!(Format::Utf8, formats("2024-01-15")[0]);
+    assert_eq!(Format::Utf8, formats("12:34:56")[0]);
+    // Text from Windows tools, with every other byte zero
+    assert_eq!(Format::Utf16Le, formats("h\0e\0l\0l\0o\0")[0]);
+    assert_eq!(Format::Utf16Be, formats("\0h\0e\0l\0l\0o")[0]);
+    assert_eq!(Format::Utf32Le, formats("h\0\0\0i\0\0\0")[0]);
+    // Russian in windows-1251, which isn't utf8
+    let russian = vec![
+        0xcf, 0xf0, 0xe8, 0xe2, 0xe5, 0xf2, 0x2c, 0x20, 0xea, 0xe0, 0xea, 0x20, 0xe4, 0xe5, 0xeb,
+        0xe0, 0x3f,
+    ];
+    let windows1251 = "windows1251".parse::<Format>().unwrap();
+    assert_eq!(windows1251, registry.infer(&russian)[0].format);
+}
+
+#[test]
+fn decode_spec() {
+    let registry = Registry::new();
+    let tests = [
+        ("hex:00ff", vec![0x00, 0xff]),
+        ("base64:aGk=", b"hi".to_vec()),
+        ("secret", b"secret".to_vec()),
+        // Not a format, so it's all part of the value
+        ("pass:word", b"pass:word".to_vec()),
+    ];
+    for (spec, expected) in tests.iter() {
+        assert_eq!(expected, &registry.decode_spec(spec, Format::Utf8).unwrap());
+    }
+    assert!(registry.decode_spec("hex:zz", Format::Utf8).is_err());
+}
+
+#[test]
+fn expect() {
+    let registry = Registry::new();
+    let sides = |expected: &str, to: &str| {
+        let to = to.parse::<Format>().unwrap();
+        match registry.expect(
+            expected,
+            Format::Utf8,
+            to,
+            DigestText::Hex,
+            b"7992739871".to_vec(),
+        ) {
+            (Ok(expected), Ok(actual)) => expected == actual,
+            (expected, actual) => panic!("{:?} {:?}", expected, actual),
+        }
+    };
+    // Check digits are compared as written
+    assert!(sides("3", "luhn"));
+    assert!(!sides("4", "luhn"));
+    assert!(sides("8", "verhoeff"));
+    // Digests as bytes, whatever the case
+    assert!(sides("7628CFCEC512BFD2E99C83B15DBC59F4", "md5"));
+    // Checksums are hex too
+    assert!(sides("D7352DDD", "crc32"));
+    assert!(sides("0xd7352ddd", "crc32"));
+    assert!(!sides("d7352dde", "crc32"));
+    assert!(!sides("00", "md5"));
+}
@@ -0,0 +1,325 @@
+use rc::codecs::error::Error;
+use rc::options::{Format, Style};
+use std::fmt::Write;
+
+// Everything there is to show after decoding the input and encoding the outputs
+pub struct Report {
+    pub source: Format,
+    pub inferred: bool,
+    // Other formats the input could have been in, with their confidence
+    pub ranked: Vec<(Format, f64)>,
+    pub outputs: Vec<Output>,
+}
+
+// The input encoded in one of the requested formats
+pub struct Output {
+    pub format: Format,
+    // Whether the encoding is text, or bytes that need escaping to be shown
+    pub printable: bool,
+    pub result: Result<Vec<u8>, Error>,
+}
+
+impl Report {
+    // The outputs that couldn't be encoded, for styles like raw that have
+    // nowhere to show them among the data
+    pub fn failures(&self) -> Vec<(Format, &Error)> {
+        self.outputs
+            .iter()
+            .filter_map(|output| output.result.as_ref().err().map(|e| (output.format, e)))
+            .collect()
+    }
+}
+
+// Renders the report in the requested style. The banner and leaders are only
+// shown to people, so we need to know whether stdout is a terminal.
+pub fn render(style: Style, report: &Report, verbosity: u64, tty: bool) -> Vec<u8> {
+    match style {
+        Style::Text => text(report, verbosity, tty),
+        Style::Json => json(report).into_bytes(),
+        Style::Table => table(report, verbosity, tty).into_bytes(),
+        Style::Raw => raw(report),
+    }
+}
+
+// Renders a failure to decode the input at all
+pub fn render_error(style: Style, source: Format, e: &Error) -> Vec<u8> {
+    match style {
+        Style::Json => format!(
+            "{{\"source\":{},\"error\":{}}}\n",
+            json_string(source.to_arg()),
+            json_error(e)
+        ),
+        _ => format!("Couldn't decode! {}\n", e),
+    }
+    .into_bytes()
+}
+
+fn banner(report: &Report, verbosity: u64, tty: bool) -> String {
+    let mut out = String::new();
+    if tty && (report.inferred || verbosity > 0) {
+        writeln!(
+            out,
+            "\t[{}{}~> {}]",
+            report.source,
+            if report.inferred { " (inferred) " } else { " " },
+            report
+                .outputs
+                .iter()
+                .map(|output| output.format.to_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .unwrap();
+        report.ranked.iter().for_each(|(format, confidence)| {
+            writeln!(out, "\t  {:.2} {}", confidence, format).unwrap();
+        });
+        out.push('\n');
+    }
+    out
+}
+
+fn text(report: &Report, verbosity: u64, tty: bool) -> Vec<u8> {
+    let do_leader = (verbosity > 0 && tty) || report.outputs.len() > 1;
+
+    // No newline if we're piping a single format, and the bytes go out
+    // untouched unless they'd land on a terminal
+    if let (false, [output]) = (do_leader, &report.outputs[..]) {
+        if let Ok(encoded) = &output.result {
+            if output.printable || !tty {
+                let mut out = banner(report, verbosity, tty).into_bytes();
+                out.extend_from_slice(encoded);
+                return out;
+            }
+        }
+    }
+
+    let mut out = banner(report, verbosity, tty);
+
+    let max_leader_length = report
+        .outputs
+        .iter()
+        .map(|output| output.format.to_str().len())
+        .max()
+        .unwrap_or(0);
+
+    report.outputs.iter().for_each(|output| {
+        let format = output.format;
+        let width = max_leader_length - format.to_str().len();
+        match (&output.result, do_leader) {
+            (Ok(encoded), true) => {
+                writeln!(
+                    out,
+                    "{}: {: >width$}\"{}\"",
+                    format,
+                    "",
+                    shown(encoded, output.printable),
+                    width = width
+                )
+            }
+            (Ok(encoded), false) => write!(out, "{}", shown(encoded, output.printable)),
+            (Err(e), true) => writeln!(
+                out,
+                "{}: {: >width$}<encoding failure>: {}",
+                format,
+                "",
+                e,
+                width = width
+            ),
+            (Err(e), false) => write!(out, "<encoding failure>: {}", e),
+        }
+        .unwrap()
+    });
+    out.into_bytes()
+}
+
+fn json(report: &Report) -> String {
+    let outputs = report
+        .outputs
+        .iter()
+        .map(|output| {
+            format!(
+                "{}:{}",
+                json_string(output.format.to_arg()),
+                match &output.result {
+                    Ok(encoded) => json_string(&shown(encoded, output.printable)),
+                    Err(e) => format!("{{\"error\":{}}}", json_error(e)),
+                }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        "{{\"source\":{},\"inferred\":{},\"outputs\":{{{}}}}}\n",
+        json_string(report.source.to_arg()),
+        report.inferred,
+        outputs
+    )
+}
+
+fn table(report: &Report, verbosity: u64, tty: bool) -> String {
+    let rows = report
+        .outputs
+        .iter()
+        .map(|output| {
+            (
+                output.format.to_str().to_string(),
+                match &output.result {
+                    Ok(encoded) => table_cell(&shown(encoded, output.printable)),
+                    Err(e) => format!("<encoding failure>: {}", e),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    let width = |header: &str, cells: Vec<&String>| {
+        cells
+            .into_iter()
+            .map(|c| c.chars().count())
+            .chain(std::iter::once(header.len()))
+            .max()
+            .unwrap()
+    };
+    let format_width = width("format", rows.iter().map(|r| &r.0).collect());
+    let output_width = width("output", rows.iter().map(|r| &r.1).collect());
+
+    let mut out = banner(report, verbosity, tty);
+    writeln!(out, "{: <fw$} | output", "format", fw = format_width).unwrap();
+    writeln!(
+        out,
+        "{}-+-{}",
+        "-".repeat(format_width),
+        "-".repeat(output_width)
+    )
+    .unwrap();
+    rows.iter().for_each(|(format, output)| {
+        writeln!(out, "{: <fw$} | {}", format, output, fw = format_width).unwrap();
+    });
+    out
+}
+
+fn raw(report: &Report) -> Vec<u8> {
+    let separate = report.outputs.len() > 1;
+    report
+        .outputs
+        .iter()
+        .flat_map(|output| {
+            let mut bytes = match &output.result {
+                Ok(encoded) => encoded.clone(),
+                // Nothing but the data goes out, so failures are left to
+                // the caller, from Report::failures
+                Err(_) => vec![],
+            };
+            if separate {
+                bytes.push(b'\n');
+            }
+            bytes
+        })
+        .collect()
+}
+
+// Output as text. Bytes that aren't text are escaped like \x8b.
+pub fn shown(encoded: &[u8], printable: bool) -> String {
+    if printable {
+        String::from_utf8_lossy(encoded).into_owned()
+    } else {
+        encoded.escape_ascii().to_string()
+    }
+}
+
+// Keeps each row of a table on one line
+fn table_cell(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\n' => "\\n".to_string(),
+            '\r' => "\\r".to_string(),
+            '\t' => "\\t".to_string(),
+            c if c.is_control() => c.escape_unicode().to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+// The error message, with the byte offset as its own field when there is one
+fn json_error(e: &Error) -> String {
+    match e.offset {
+        Some(offset) => format!("{},\"offset\":{}", json_string(&e.err), offset),
+        None => json_string(&e.err),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    s.chars().for_each(|c| match c {
+        '"' => out.push_str("\\\""),
+        '\\' => out.push_str("\\\\"),
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\t' => out.push_str("\\t"),
+        c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+        c => out.push(c),
+    });
+    out.push('"');
+    out
+}
+
+#[test]
+fn render_styles() {
+    let report = Report {
+        source: Format::Hex,
+        inferred: true,
+        ranked: vec![],
+        outputs: vec![
+            Output {
+                format: Format::Utf8,
+                printable: true,
+                result: Ok(b"hi \"rc\"\n".to_vec()),
+            },
+            Output {
+                format: Format::Base64,
+                printable: true,
+                result: Ok(b"aGkgInJjIgo=".to_vec()),
+            },
+            Output {
+                format: Format::Spelling,
+                printable: true,
+                result: Err(Error::new("input data is not ascii".to_string())),
+            },
+        ],
+    };
+
+    assert_eq!(
+        "{\"source\":\"hex\",\"inferred\":true,\"outputs\":{\"utf8\":\"hi \\\"rc\\\"\\n\",\"base64\":\"aGkgInJjIgo=\",\"spelling\":{\"error\":\"input data is not ascii\"}}}\n",
+        String::from_utf8(render(Style::Json, &report, 0, false)).unwrap()
+    );
+    assert_eq!(
+        "format   | output\n---------+--------------------------------------------\nutf8     | hi \"rc\"\\n\nbase 64  | aGkgInJjIgo=\nspelling | <encoding failure>: input data is not ascii\n",
+        String::from_utf8(render(Style::Table, &report, 0, false)).unwrap()
+    );
+    assert_eq!(
+        "hi \"rc\"\n\naGkgInJjIgo=\n\n".as_bytes().to_vec(),
+        render(Style::Raw, &report, 0, false)
+    );
+    let failures = report.failures();
+    assert_eq!(1, failures.len());
+    assert_eq!(Format::Spelling, failures[0].0);
+
+    // Binary output is left alone unless it would be shown on a terminal
+    let binary = Report {
+        source: Format::Hex,
+        inferred: false,
+        ranked: vec![],
+        outputs: vec![Output {
+            format: Format::Raw,
+            printable: false,
+            result: Ok(vec![0x1f, 0x8b, b'a']),
+        }],
+    };
+    assert_eq!(
+        vec![0x1f, 0x8b, b'a'],
+        render(Style::Text, &binary, 0, false)
+    );
+    assert_eq!(
+        b"\\x1f\\x8ba".to_vec(),
+        render(Style::Text, &binary, 0, true)
+    );
+}
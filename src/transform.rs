@@ -0,0 +1,181 @@
+use crate::codecs::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Rewrites text, rather than converting between representations of the
+// same bytes like a codec does. Input has to be utf8.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Transform {
+    // By grapheme cluster, so accents and emoji stay whole
+    Reverse,
+    Upper,
+    Lower,
+    // Capitalizes each word, leaving words in capitals alone as acronyms
+    Title,
+    Camel,
+    Snake,
+    Kebab,
+}
+
+impl Transform {
+    pub fn all_variants() -> Vec<&'static str> {
+        vec![
+            "reverse", "upper", "lower", "title", "camel", "snake", "kebab",
+        ]
+    }
+
+    pub fn apply(self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let s = String::from_utf8(data)
+            .map_err(|_| Error::new("input data is not utf8".to_string()))?;
+        let out = match self {
+            Self::Reverse => s.graphemes(true).rev().collect(),
+            Self::Upper => s.to_uppercase(),
+            Self::Lower => s.to_lowercase(),
+            Self::Title => title(&s),
+            Self::Camel => words(&s)
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    if i == 0 {
+                        word.to_lowercase()
+                    } else {
+                        capitalize(&word.to_lowercase())
+                    }
+                })
+                .collect(),
+            Self::Snake => join_lower(&s, "_"),
+            Self::Kebab => join_lower(&s, "-"),
+        };
+        Ok(out.into_bytes())
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::Reverse => "reverse",
+            Self::Upper => "uppercase",
+            Self::Lower => "lowercase",
+            Self::Title => "title case",
+            Self::Camel => "camelCase",
+            Self::Snake => "snake_case",
+            Self::Kebab => "kebab-case",
+        }
+    }
+}
+
+// Splits text into words for camelCase and the like. Anything that isn't a
+// letter or digit separates words, and so do changes of case: "HTTPServer2"
+// is "HTTP" and "Server2". Digits stay with the word they follow.
+fn words(s: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let chars = s.chars().collect::<Vec<_>>();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let prev = word.chars().last();
+        let next = chars.get(i + 1);
+        let starts_word = c.is_uppercase()
+            && match prev {
+                Some(p) if p.is_lowercase() || p.is_numeric() => true,
+                // The last capital of an acronym starts the next word
+                Some(p) if p.is_uppercase() => next.is_some_and(|n| n.is_lowercase()),
+                _ => false,
+            };
+        if starts_word {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn join_lower(s: &str, separator: &str) -> String {
+    words(s)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// Keeps the spacing as it was, and only touches the letters of each word
+fn title(s: &str) -> String {
+    s.split_word_bounds()
+        .map(|word| {
+            if word.chars().any(|c| c.is_lowercase()) {
+                capitalize(&word.to_lowercase())
+            } else {
+                word.to_string()
+            }
+        })
+        .collect()
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+impl FromStr for Transform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "reverse" => Ok(Self::Reverse),
+            "upper" => Ok(Self::Upper),
+            "lower" => Ok(Self::Lower),
+            "title" => Ok(Self::Title),
+            "camel" => Ok(Self::Camel),
+            "snake" => Ok(Self::Snake),
+            "kebab" => Ok(Self::Kebab),
+            _ => Err(Error::new(format!("Unknown transform {}", s))),
+        }
+    }
+}
+
+#[test]
+fn apply() {
+    let tests = vec![
+        (Transform::Reverse, "hello", "olleh"),
+        // Combining accent and a family emoji made of several code points
+        (Transform::Reverse, "cafe\u{301} 👨‍👩‍👧", "👨‍👩‍👧 e\u{301}fac"),
+        (Transform::Upper, "straße", "STRASSE"),
+        (Transform::Lower, "HeLLo", "hello"),
+        (Transform::Title, "hello wide world", "Hello Wide World"),
+        (Transform::Title, "the NASA launch", "The NASA Launch"),
+        (Transform::Camel, "hello wide world", "helloWideWorld"),
+        (Transform::Camel, "XMLHttpRequest", "xmlHttpRequest"),
+        (Transform::Camel, "snake_case_name", "snakeCaseName"),
+        (Transform::Snake, "helloWideWorld", "hello_wide_world"),
+        (Transform::Snake, "HTTPServer2Error", "http_server2_error"),
+        (Transform::Snake, "utf8Decoder", "utf8_decoder"),
+        (Transform::Snake, "  Hello, World!  ", "hello_world"),
+        (Transform::Kebab, "Some Title Here", "some-title-here"),
+        (Transform::Kebab, "parseURLQuery", "parse-url-query"),
+    ];
+
+    for (transform, input, expected) in tests {
+        assert_eq!(
+            expected.as_bytes().to_vec(),
+            transform.apply(input.as_bytes().to_vec()).unwrap()
+        );
+    }
+
+    assert!(Transform::Upper.apply(vec![0xff]).is_err());
+}